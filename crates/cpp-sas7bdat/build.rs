@@ -29,6 +29,16 @@ fn generate_bindings(out_dir: &PathBuf) {
         .allowlist_type("SasArrowErrorCode")
         .allowlist_type("SasArrowReaderInfo")
         .allowlist_type("SasArrowColumnInfo")
+        .allowlist_type("SasArrowReadOptions")
+        .allowlist_type("SasArrowFormatMapping")
+        .allowlist_type("SasArrowTemporalType")
+        .allowlist_type("SasArrowStringLayout")
+        .allowlist_type("SasArrowErrorPolicy")
+        .allowlist_type("SasArrowDiagnostic")
+        .allowlist_type("SasArrowBatchPosition")
+        .allowlist_type("SasArrowColumnMetadata")
+        .allowlist_type("SasArrowDatasetMetadata")
+        .allowlist_type("SasArrowDataSource")
         .allowlist_type("ArrowArray")
         .allowlist_type("ArrowSchema")
         .allowlist_var("SAS_ARROW_.*")
@@ -0,0 +1,249 @@
+use std::fs::File;
+#[cfg(feature = "zip")]
+use std::io::BufReader;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::compression::{self, Codec, Decompressed};
+
+/// A SAS dataset stored in a ZIP or TAR archive, see `SasReader::read_archive_member_metadata`
+/// for its SAS metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SasArchiveMember {
+    /// Path of the member inside the archive
+    pub name: String,
+    /// Size of the SAS file in bytes
+    pub size: u64,
+    /// Size of the member in the archive, before decompression.
+    /// Equal to `size` in a TAR archive, compressed as a whole.
+    pub compressed_size: u64,
+}
+
+/// Seekable stream of a member, read by the C++ reader through callbacks
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    /// TAR archive, compressed as a whole with the codec
+    Tar(Option<Codec>),
+}
+
+// Where the data of a member is
+enum Location {
+    /// Uncompressed bytes at this offset of the archive, decompressed with the codec
+    Plain { codec: Option<Codec>, start: u64 },
+    /// Deflated ZIP member data at this offset of the archive
+    #[cfg(feature = "zip")]
+    Deflated { start: u64 },
+    /// Listed, but cannot be read (encrypted, unsupported compression method)
+    #[cfg(feature = "zip")]
+    Unsupported(&'static str),
+}
+
+struct Entry {
+    member: SasArchiveMember,
+    location: Location,
+}
+
+fn is_sas_member(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with(".sas7bdat")
+}
+
+#[cfg(not(all(feature = "zip", feature = "tar")))]
+fn unsupported(archive: &str, feature: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::Unsupported,
+        format!("{} archive, enable the `{}` feature to read it", archive, feature),
+    )
+}
+
+// The archive stream, decompressed when the TAR archive is compressed as a whole
+fn open_stream(path: &Path, codec: Option<Codec>) -> io::Result<Box<dyn ReadSeek>> {
+    match codec {
+        None => Ok(Box::new(File::open(path)?)),
+        Some(codec) => Ok(Box::new(Decompressed::open(path, codec)?)),
+    }
+}
+
+fn archive_kind(path: &Path) -> io::Result<Option<ArchiveKind>> {
+    let mut magic = [0u8; 4];
+    let length = compression::read_prefix(&mut File::open(path)?, &mut magic)?;
+    if magic[..length].starts_with(b"PK\x03\x04") || magic[..length].starts_with(b"PK\x05\x06") {
+        return Ok(Some(ArchiveKind::Zip));
+    }
+
+    // The TAR header holds the "ustar" magic at offset 257
+    let codec = compression::detect(path);
+    let mut header = [0u8; 262];
+    let length = compression::read_prefix(&mut open_stream(path, codec)?, &mut header)?;
+    if length == header.len() && &header[257..] == b"ustar" {
+        return Ok(Some(ArchiveKind::Tar(codec)));
+    }
+    Ok(None)
+}
+
+fn entries(path: &Path) -> io::Result<Vec<Entry>> {
+    match archive_kind(path)? {
+        Some(ArchiveKind::Zip) => zip_entries(path),
+        Some(ArchiveKind::Tar(codec)) => tar_entries(path, codec),
+        None => Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{} is not a ZIP or TAR archive", path.display()),
+        )),
+    }
+}
+
+#[cfg(feature = "zip")]
+fn zip_entries(path: &Path) -> io::Result<Vec<Entry>> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        // Raw access: the data is decompressed by the member stream
+        let file = archive.by_index_raw(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let start = file.data_start();
+        let location = match file.compression() {
+            _ if file.encrypted() => Location::Unsupported("encrypted members cannot be read"),
+            zip::CompressionMethod::Stored => Location::Plain { codec: None, start },
+            zip::CompressionMethod::Deflated => Location::Deflated { start },
+            _ => Location::Unsupported("only stored and deflated members can be read"),
+        };
+        entries.push(Entry {
+            member: SasArchiveMember {
+                name: file.name().to_string(),
+                size: file.size(),
+                compressed_size: file.compressed_size(),
+            },
+            location,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(not(feature = "zip"))]
+fn zip_entries(_path: &Path) -> io::Result<Vec<Entry>> {
+    Err(unsupported("ZIP", "zip"))
+}
+
+#[cfg(feature = "tar")]
+fn tar_entries(path: &Path, codec: Option<Codec>) -> io::Result<Vec<Entry>> {
+    let mut archive = tar::Archive::new(open_stream(path, codec)?);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        entries.push(Entry {
+            member: SasArchiveMember {
+                name: entry.path()?.to_string_lossy().into_owned(),
+                size: entry.size(),
+                compressed_size: entry.size(),
+            },
+            location: Location::Plain { codec, start: entry.raw_file_position() },
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(not(feature = "tar"))]
+fn tar_entries(_path: &Path, _codec: Option<Codec>) -> io::Result<Vec<Entry>> {
+    Err(unsupported("TAR", "tar"))
+}
+
+/// The SAS datasets (`.sas7bdat` members) of a ZIP or TAR archive
+pub(crate) fn list_members(path: &Path) -> io::Result<Vec<SasArchiveMember>> {
+    Ok(entries(path)?
+        .into_iter()
+        .map(|entry| entry.member)
+        .filter(|member| is_sas_member(&member.name))
+        .collect())
+}
+
+/// Stream of the member `name` of the archive, decompressed while read
+pub(crate) fn open_member(path: &Path, name: &str) -> io::Result<Box<dyn ReadSeek>> {
+    let entry = entries(path)?
+        .into_iter()
+        .find(|entry| entry.member.name == name)
+        .ok_or_else(|| io::Error::new(
+            ErrorKind::NotFound,
+            format!("{} not found in the archive {}", name, path.display()),
+        ))?;
+
+    match entry.location {
+        Location::Plain { codec, start } => {
+            Ok(Box::new(Window::new(open_stream(path, codec)?, start, entry.member.size)?))
+        }
+        #[cfg(feature = "zip")]
+        Location::Deflated { start } => {
+            let path = path.to_path_buf();
+            let length = entry.member.compressed_size;
+            Ok(Box::new(Decompressed::new(move || {
+                let data = Window::new(File::open(&path)?, start, length)?;
+                Ok(Box::new(flate2::read::DeflateDecoder::new(BufReader::new(data))) as Box<dyn Read + Send>)
+            })?))
+        }
+        #[cfg(feature = "zip")]
+        Location::Unsupported(reason) => Err(io::Error::new(ErrorKind::Unsupported, format!("{}: {}", name, reason))),
+    }
+}
+
+/// Split a path going through an archive (`data/vendor.zip/dir/member.sas7bdat`) into
+/// the archive path and the member name. None for a path that is not inside an archive.
+pub(crate) fn split_path(path: &Path) -> Option<(PathBuf, String)> {
+    if path.exists() {
+        return None;
+    }
+    let archive = path.ancestors().skip(1).find(|ancestor| ancestor.is_file())?;
+    archive_kind(archive).ok()??;
+    let member = path.strip_prefix(archive).ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((archive.to_path_buf(), member))
+}
+
+/// `length` bytes of `inner` from `start`, seen as a whole stream
+struct Window<R> {
+    inner: R,
+    start: u64,
+    length: u64,
+    position: u64,
+}
+
+impl<R: Seek> Window<R> {
+    fn new(mut inner: R, start: u64, length: u64) -> io::Result<Self> {
+        inner.seek(SeekFrom::Start(start))?;
+        Ok(Window { inner, start, length, position: 0 })
+    }
+}
+
+impl<R: Read> Read for Window<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.length.saturating_sub(self.position);
+        let max = buf.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Window<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.length.checked_add_signed(delta),
+        };
+        let target = target.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        self.inner.seek(SeekFrom::Start(self.start + target))?;
+        self.position = target;
+        Ok(target)
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use polars::prelude::*;
+
+use crate::xport::text;
+
+// Bytes 12-32 of the magic number, byte 15 differs between data sets and catalogs
+const MAGIC_NUMBER_TAIL: [u8; 16] = [
+    0xb3, 0x14, 0x11, 0xcf, 0xbd, 0x92, 0x08, 0x00, 0x09, 0xc7, 0x31, 0x8c, 0x18, 0x1f, 0x10, 0x11,
+];
+const MIN_HEADER_LENGTH: usize = 1024;
+// Flags of the first byte of an entry: its start is `LOW`, its end `HIGH`, or it is `OTHER`
+const ENTRY_LOW: u8 = 0x40;
+const ENTRY_HIGH: u8 = 0x20;
+const ENTRY_OTHER: u8 = 0x10;
+
+/// Values mapped to a label by a format of a catalog
+#[derive(Debug, Clone, PartialEq)]
+pub enum SasRange {
+    /// Numbers from the first to the last bound, both included; `None` for `LOW` and `HIGH`
+    Numbers(Option<f64>, Option<f64>),
+    /// A missing value by the character after its dot: `.`, `_` or `A`-`Z`
+    Missing(char),
+    /// Strings from the first to the last bound, both included, without their trailing blanks;
+    /// `None` for `LOW` and `HIGH`
+    Strings(Option<String>, Option<String>),
+    /// `OTHER`, the values of no other range
+    Other,
+}
+
+/// Values and their labels, as a format maps them
+pub type SasValueLabels = Vec<(SasRange, String)>;
+
+/// Formats of a `.sas7bcat` catalog, the labels of their values in the order of the catalog
+///
+/// Numeric ranges are read from their sortable keys (big-endian with the sign bit flipped for
+/// positive numbers and every bit for negative numbers and missing values). `LOW`, `HIGH` and
+/// `OTHER` are read from the flags of the entries; the smallest and the largest keys also
+/// stand for `LOW` and `HIGH`.
+#[derive(Debug, Clone, Default)]
+pub struct SasCatalog {
+    /// Name of the catalog, e.g. `FORMATS`
+    pub name: String,
+    /// Format name (starting with `$` for character formats) -> values and their labels
+    pub formats: HashMap<String, SasValueLabels>,
+}
+
+impl SasCatalog {
+    pub fn open(file_path: &str) -> PolarsResult<Self> {
+        Self::from_bytes(&std::fs::read(file_path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> PolarsResult<Self> {
+        if bytes.len() < MIN_HEADER_LENGTH || bytes[..12] != [0; 12] || bytes[16..32] != MAGIC_NUMBER_TAIL {
+            return Err(PolarsError::ComputeError("Not a SAS file".into()));
+        }
+        if &bytes[156..164] != b"CATALOG " {
+            return Err(PolarsError::ComputeError(
+                format!("Not a SAS catalog but a {} file", text(&bytes[156..164])).into(),
+            ));
+        }
+        let layout = Layout::new(bytes)?;
+        let mut formats = HashMap::new();
+        for (page, position) in layout.blocks()? {
+            let block = layout.block(page, position)?;
+            if let Some((name, ranges)) = layout.format(&block)? {
+                formats.insert(name, ranges);
+            }
+        }
+        Ok(SasCatalog { name: text(&bytes[92..156]), formats })
+    }
+
+    /// Value labels given as a JSON object of formats, each an object of values and their
+    /// labels. Values of numeric formats are numbers, `LOW-HIGH` style ranges (`1-5`,
+    /// `LOW-0`, `10-HIGH`), `OTHER`, `.` or the special missing values `._` and `.A`-`.Z`;
+    /// values of character formats (`$` first) are strings or `OTHER`:
+    /// `{"SEXF": {"1": "Male", "2": "Female", ".": "Unknown"}, "$YN": {"Y": "Yes", "N": "No"}}`.
+    /// The labels keep the order of the object: a value is labelled by the first range holding it.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> PolarsResult<Self> {
+        let invalid = |message: String| PolarsError::ComputeError(format!("Invalid value labels: {}", message).into());
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        let Some(object) = value.as_object() else {
+            return Err(invalid("not an object of formats".to_string()));
+        };
+        let mut formats = HashMap::new();
+        for (format, labels) in object {
+            let Some(labels) = labels.as_object() else {
+                return Err(invalid(format!("the labels of {} are not an object", format)));
+            };
+            let ranges = labels
+                .iter()
+                .map(|(value, label)| {
+                    let range = parse_range(value, format.starts_with('$')).map_err(invalid)?;
+                    match label.as_str() {
+                        Some(label) => Ok((range, label.to_string())),
+                        None => Err(invalid(format!("the label of {} in {} is not a string", value, format))),
+                    }
+                })
+                .collect::<PolarsResult<_>>()?;
+            formats.insert(format.to_ascii_uppercase(), ranges);
+        }
+        Ok(SasCatalog { name: String::new(), formats })
+    }
+
+    /// Values and labels of a format, by its name with or without a width, e.g. `$SEX` or `SEXF8.`
+    pub fn get(&self, format: &str) -> Option<&[(SasRange, String)]> {
+        let name = format.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.').to_ascii_uppercase();
+        self.formats.get(&name).map(Vec::as_slice)
+    }
+}
+
+// A value or a range of values of the `PROC FORMAT` syntax
+#[cfg(feature = "json")]
+fn parse_range(value: &str, character: bool) -> Result<SasRange, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("OTHER") {
+        return Ok(SasRange::Other);
+    }
+    if character {
+        return Ok(SasRange::Strings(Some(value.to_string()), Some(value.to_string())));
+    }
+    if let Some(tag) = value.strip_prefix('.') {
+        return match tag.to_ascii_uppercase().as_bytes() {
+            [] => Ok(SasRange::Missing('.')),
+            [tag @ (b'A'..=b'Z' | b'_')] => Ok(SasRange::Missing(*tag as char)),
+            _ => Err(format!("invalid missing value {}", value)),
+        };
+    }
+    let bound = |bound: &str| match bound.trim() {
+        bound if bound.eq_ignore_ascii_case("LOW") || bound.eq_ignore_ascii_case("HIGH") => Ok(None),
+        bound => bound.parse::<f64>().map(Some).map_err(|_| format!("invalid value {}", value)),
+    };
+    // The dash between the bounds follows the first bound, not a sign or an exponent
+    let bytes = value.as_bytes();
+    let dash = (1..bytes.len()).find(|&i| bytes[i] == b'-' && !matches!(bytes[i - 1], b'-' | b'e' | b'E'));
+    match dash {
+        Some(dash) => Ok(SasRange::Numbers(bound(&value[..dash])?, bound(&value[dash + 1..])?)),
+        None => {
+            let value = bound(value)?;
+            Ok(SasRange::Numbers(value, value))
+        }
+    }
+}
+
+// Bitness, alignment and byte order of a catalog, as `header.hpp` reads them for data sets
+struct Layout<'a> {
+    bytes: &'a [u8],
+    is_64bit: bool,
+    align1: usize,
+    is_big_endian: bool,
+    header_length: usize,
+    page_length: usize,
+    page_count: usize,
+}
+
+impl<'a> Layout<'a> {
+    fn new(bytes: &'a [u8]) -> PolarsResult<Self> {
+        let mut layout = Layout {
+            bytes,
+            is_64bit: bytes[32] == b'3',
+            align1: if bytes[35] == b'3' { 4 } else { 0 },
+            is_big_endian: bytes[37] != 0x01,
+            header_length: 0,
+            page_length: 0,
+            page_count: 0,
+        };
+        layout.header_length = layout.u32(bytes, 196 + layout.align1)?;
+        layout.page_length = layout.u32(bytes, 200 + layout.align1)?;
+        layout.page_count = layout.u32(bytes, 204 + layout.align1)?;
+        if layout.header_length < MIN_HEADER_LENGTH || layout.page_length == 0 {
+            return Err(PolarsError::ComputeError("Invalid SAS catalog header".into()));
+        }
+        Ok(layout)
+    }
+
+    fn uint<const N: usize>(&self, bytes: &[u8], offset: usize) -> PolarsResult<usize> {
+        let mut value: [u8; N] = slice(bytes, offset, N)?.try_into().unwrap();
+        if !self.is_big_endian {
+            value.reverse();
+        }
+        Ok(value.iter().fold(0, |n, &b| (n << 8) | b as usize))
+    }
+
+    fn u16(&self, bytes: &[u8], offset: usize) -> PolarsResult<usize> {
+        self.uint::<2>(bytes, offset)
+    }
+
+    fn u32(&self, bytes: &[u8], offset: usize) -> PolarsResult<usize> {
+        self.uint::<4>(bytes, offset)
+    }
+
+    fn u64(&self, bytes: &[u8], offset: usize) -> PolarsResult<usize> {
+        self.uint::<8>(bytes, offset)
+    }
+
+    fn page(&self, index: usize) -> PolarsResult<&'a [u8]> {
+        slice(self.bytes, self.header_length + index * self.page_length, self.page_length)
+    }
+
+    // Pages and positions of the first links of the format blocks, from the `XLSR` index
+    // starting in the first page and continued on the pages starting with `XLSR`
+    fn blocks(&self) -> PolarsResult<Vec<(usize, usize)>> {
+        let (mut offset, mut record_length, mut kind_offset) =
+            (856 + 2 * self.align1, 212 + self.align1, 50 + self.align1);
+        if self.is_64bit {
+            offset += 144;
+            record_length += 72;
+            kind_offset += 24;
+        }
+        let mut blocks = Vec::new();
+        let first = self.page(0)?;
+        self.index(first.get(offset..).unwrap_or_default(), record_length, kind_offset, &mut blocks)?;
+        for index in 1..self.page_count {
+            let page = self.page(index)?;
+            if &page[16..20] == b"XLSR" {
+                self.index(&page[16..], record_length, kind_offset, &mut blocks)?;
+            }
+        }
+        blocks.sort_unstable();
+        blocks.dedup();
+        Ok(blocks)
+    }
+
+    fn index(
+        &self,
+        records: &[u8],
+        record_length: usize,
+        kind_offset: usize,
+        blocks: &mut Vec<(usize, usize)>,
+    ) -> PolarsResult<()> {
+        let mut x = 0;
+        while x + record_length <= records.len() {
+            // Some records follow 8 bytes of padding
+            if &records[x..x + 4] != b"XLSR" {
+                x += 8;
+            }
+            match records.get(x..x + 4) {
+                Some(b"    ") => {
+                    x += record_length;
+                    continue;
+                }
+                Some(b"XLSR") => {}
+                _ => break,
+            }
+            // Formats are the records of kind `O`
+            if records.get(x + kind_offset) == Some(&b'O') {
+                blocks.push(match self.is_64bit {
+                    true => (self.u64(records, x + 8)?, self.u16(records, x + 16)?),
+                    false => (self.u32(records, x + 4)?, self.u16(records, x + 8)?),
+                });
+            }
+            x += record_length;
+        }
+        Ok(())
+    }
+
+    // A block chained over several pages: each link starts with the page (1 for the first
+    // page after the header) and the position of the next link, then its own length
+    fn block(&self, mut page: usize, mut position: usize) -> PolarsResult<Vec<u8>> {
+        let link_length = if self.is_64bit { 32 } else { 16 };
+        let mut block = Vec::new();
+        let mut links = 0;
+        while page > 0 && position > 0 && page <= self.page_count && links < self.page_count {
+            links += 1;
+            let start = self.header_length + (page - 1) * self.page_length + position;
+            let link = slice(self.bytes, start, link_length)?;
+            let length = if self.is_64bit {
+                (page, position) = (self.u32(link, 0)?, self.u16(link, 8)?);
+                self.u16(link, 10)?
+            } else {
+                (page, position) = (self.u32(link, 0)?, self.u16(link, 4)?);
+                self.u16(link, 6)?
+            };
+            block.extend_from_slice(slice(self.bytes, start + link_length, length)?);
+        }
+        Ok(block)
+    }
+
+    // A format block: its name, the count of its entries, the entries (values and the index
+    // of their label) and the labels
+    fn format(&self, block: &[u8]) -> PolarsResult<Option<(String, SasValueLabels)>> {
+        if block.len() < 106 {
+            return Ok(None);
+        }
+        let flags = self.u16(block, 2)?;
+        let mut pad = if flags & 0x08 != 0 { 4 } else { 0 };
+        let (capacity, used, mut payload) = match self.is_64bit {
+            true => (self.u64(block, 42 + pad)?, self.u64(block, 50 + pad)?, 106 + 32),
+            false => (self.u32(block, 38 + pad)?, self.u32(block, 42 + pad)?, 106),
+        };
+        let mut name = text(&block[8..16]);
+        if pad > 0 {
+            pad += 16;
+        }
+        // Names longer than 8 characters follow
+        let long_name = if self.is_64bit { 0x20 } else { 0x80 };
+        if flags & long_name != 0 {
+            name = text(slice(block, payload + pad, 32)?);
+            pad += 32;
+        }
+        payload += pad;
+        if used > capacity {
+            return Err(PolarsError::ComputeError(format!("Invalid entries of the format {}", name).into()));
+        }
+
+        let character = name.starts_with('$');
+        let mut position = payload;
+        let mut values = Vec::with_capacity(used);
+        for i in 0..capacity {
+            let length = 6 + self.u16(block, position + 2)?;
+            let entry = slice(block, position, length)?;
+            if i < used {
+                values.push((self.u32(entry, 10 + self.align1)?, range(entry, character)?));
+            }
+            position += length;
+        }
+        let mut labels = Vec::with_capacity(used);
+        for _ in 0..used {
+            let length = self.u16(block, position + 8)?;
+            labels.push(text(slice(block, position + 10, length)?));
+            position += 11 + length;
+        }
+
+        let ranges = values
+            .into_iter()
+            .map(|(label, range)| match labels.get(label) {
+                Some(label) => Ok((range, label.clone())),
+                None => Err(PolarsError::ComputeError(format!("Invalid label of the format {}", name).into())),
+            })
+            .collect::<PolarsResult<_>>()?;
+        Ok(Some((name, ranges)))
+    }
+}
+
+fn slice(bytes: &[u8], offset: usize, length: usize) -> PolarsResult<&[u8]> {
+    bytes
+        .get(offset..offset + length)
+        .ok_or_else(|| PolarsError::ComputeError("Truncated SAS catalog".into()))
+}
+
+// The values of an entry: character values take the last 16 bytes, numeric ones are keys of
+// 8 bytes for the start and the end. The flags of the entry mark `OTHER` and the `LOW` and
+// `HIGH` bounds, which older catalogs only give as the smallest and the largest keys.
+fn range(entry: &[u8], character: bool) -> PolarsResult<SasRange> {
+    let flags = entry[0];
+    if flags & ENTRY_OTHER != 0 {
+        return Ok(SasRange::Other);
+    }
+    let (low, high) = (flags & ENTRY_LOW != 0, flags & ENTRY_HIGH != 0);
+    if character {
+        let value = &entry[entry.len().saturating_sub(16)..];
+        let value = match value.iter().all(|&b| b == 0) {
+            true => None,
+            false => Some(text(value)),
+        };
+        return Ok(SasRange::Strings(value.clone().filter(|_| !low), value.filter(|_| !high)));
+    }
+    let start = key(slice(entry, 22, 8)?);
+    let end = entry.get(30..38).map_or(start, key);
+    Ok(match (start, end) {
+        (Key::Missing(tag), _) if !low => SasRange::Missing(tag),
+        (start, end) => SasRange::Numbers(start.bound().filter(|_| !low), end.bound().filter(|_| !high)),
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Key {
+    Low,
+    High,
+    Number(f64),
+    Missing(char),
+}
+
+impl Key {
+    fn bound(self) -> Option<f64> {
+        match self {
+            Key::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn key(bytes: &[u8]) -> Key {
+    let key = u64::from_be_bytes(bytes.try_into().unwrap());
+    match key {
+        0 => return Key::Low,
+        u64::MAX => return Key::High,
+        _ => {}
+    }
+    let bits = if key >> 63 == 1 { key & !(1 << 63) } else { !key };
+    let value = f64::from_bits(bits);
+    if !value.is_nan() {
+        return Key::Number(value);
+    }
+    // The complement of the fifth byte: 0 for `._`, 1 for `.`, then `.A`-`.Z`, or their characters
+    Key::Missing(match !((bits >> 40) as u8) {
+        0 => '_',
+        code @ 2..=27 => (b'A' + code - 2) as char,
+        code @ (b'A'..=b'Z' | b'_') => code as char,
+        _ => '.',
+    })
+}
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Compression codec of a whole `.sas7bdat` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Codec {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Codec {
+    fn from_magic(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Codec::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Codec::Zstd)
+        } else if magic.starts_with(b"BZh") {
+            Some(Codec::Bzip2)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Codec::Xz)
+        } else {
+            None
+        }
+    }
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gz" | "gzip" => Some(Codec::Gzip),
+            "zst" | "zstd" => Some(Codec::Zstd),
+            "bz2" => Some(Codec::Bzip2),
+            "xz" => Some(Codec::Xz),
+            _ => None,
+        }
+    }
+
+    fn feature(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zstd => "zstd",
+            Codec::Bzip2 => "bzip2",
+            Codec::Xz => "xz",
+        }
+    }
+
+    pub(crate) fn decoder<R: Read + Send + 'static>(self, reader: R) -> io::Result<Box<dyn Read + Send>> {
+        let file = BufReader::new(reader);
+        match self {
+            #[cfg(feature = "gzip")]
+            Codec::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(file))),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(file)?)),
+            #[cfg(feature = "bzip2")]
+            Codec::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(file))),
+            #[cfg(feature = "xz")]
+            Codec::Xz => Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(file))),
+            #[allow(unreachable_patterns)]
+            codec => {
+                drop(file);
+                Err(io::Error::new(
+                    ErrorKind::Unsupported,
+                    format!("{:?} compressed file, enable the `{}` feature to read it", codec, codec.feature()),
+                ))
+            }
+        }
+    }
+}
+
+/// Detect a compressed file from its magic bytes, then from its extension.
+/// Returns None for a file that cannot be opened: the C++ reader reports the error.
+pub(crate) fn detect(path: &Path) -> Option<Codec> {
+    let mut magic = [0u8; 6];
+    let length = read_prefix(&mut File::open(path).ok()?, &mut magic).ok()?;
+    Codec::from_magic(&magic[..length]).or_else(|| Codec::from_extension(path))
+}
+
+/// Fill `buf` from the start of `reader`, returns the number of bytes read (less at the end of the data)
+pub(crate) fn read_prefix(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut length = 0;
+    while length < buf.len() {
+        match reader.read(&mut buf[length..]) {
+            Ok(0) => break,
+            Ok(n) => length += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(length)
+}
+
+// Opens the decompressed stream from its start
+type Open = Box<dyn Fn() -> io::Result<Box<dyn Read + Send>> + Send>;
+
+/// Decompressed content of a file, streamed.
+///
+/// The reader only reads forward once the metadata is parsed; seeking backwards
+/// restarts the decompression from the start of the file, seeking forwards skips data.
+pub(crate) struct Decompressed {
+    open: Open,
+    decoder: Box<dyn Read + Send>,
+    position: u64,
+}
+
+impl Decompressed {
+    pub(crate) fn open(path: &Path, codec: Codec) -> io::Result<Self> {
+        let path = path.to_path_buf();
+        Self::new(move || codec.decoder(File::open(&path)?))
+    }
+
+    /// Stream the data returned by `open`, which is called again for each restart
+    pub(crate) fn new(open: impl Fn() -> io::Result<Box<dyn Read + Send>> + Send + 'static) -> io::Result<Self> {
+        let decoder = open()?;
+        Ok(Decompressed { open: Box::new(open), decoder, position: 0 })
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        self.decoder = (self.open)()?;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl Read for Decompressed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.decoder.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Decompressed {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(_) => {
+                return Err(io::Error::new(ErrorKind::Unsupported, "cannot seek from the end of a compressed stream"));
+            }
+        };
+        let target = target.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        if target < self.position {
+            self.restart()?;
+        }
+        // Stops early at the end of the data, like reading past the end of a file
+        let remaining = target - self.position;
+        io::copy(&mut self.by_ref().take(remaining), &mut io::sink())?;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,167 @@
+// Compressors of the rows of a `.sas7bdat` file, the inverse of the `RLE` (`SASYZCRL`) and
+// `RDC` (`SASYZCR2`) decompressors of `vendor/src/decompressors.hpp`
+
+// Commands of the RLE compression (high nibble of the first byte)
+const RLE_COPY64: u8 = 0x0;
+const RLE_INSERT_BYTE18: u8 = 0x4;
+const RLE_COPY1: u8 = 0x8;
+const RLE_INSERT_BYTE3: u8 = 0xC;
+
+// Bytes with their own RLE commands: (byte, command for 17 or more, command for 2 to 17)
+const RLE_SPECIAL_BYTES: [(u8, u8, u8); 3] = [(b'@', 0x5, 0xD), (b' ', 0x6, 0xE), (0x00, 0x7, 0xF)];
+
+// Length of the run of `bytes[0]` at the start of `bytes`, at most `limit`
+fn run_length(bytes: &[u8], limit: usize) -> usize {
+    bytes.iter().take(limit).take_while(|&&b| b == bytes[0]).count()
+}
+
+fn rle_copy(out: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(4159) {
+        let n = chunk.len();
+        if n >= 64 {
+            out.extend([(RLE_COPY64 << 4) | ((n - 64) >> 8) as u8, (n - 64) as u8]);
+        } else {
+            // COPY1, COPY17, COPY33 and COPY49 follow each other
+            out.push(((RLE_COPY1 + ((n - 1) / 16) as u8) << 4) | ((n - 1) % 16) as u8);
+        }
+        out.extend_from_slice(chunk);
+    }
+}
+
+// Split a run of `length` bytes in runs of at most `max` bytes, none shorter than `min`
+fn run_chunks(mut length: usize, min: usize, max: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if length == 0 {
+            return None;
+        }
+        let mut n = length.min(max);
+        if length - n > 0 && length - n < min {
+            n = length - min;
+        }
+        length -= n;
+        Some(n)
+    })
+}
+
+/// Compress a row with the `SASYZCRL` run-length encoding
+pub(crate) fn rle_compress(row: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(row.len());
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < row.len() {
+        let byte = row[i];
+        let length = run_length(&row[i..], row.len());
+        let special = RLE_SPECIAL_BYTES.iter().find(|(b, _, _)| *b == byte);
+        let min = if special.is_some() { 2 } else { 3 };
+        if length < min {
+            i += length;
+            continue;
+        }
+        rle_copy(&mut out, &row[literal_start..i]);
+        match special {
+            Some(&(_, long, short)) => {
+                for n in run_chunks(length, min, 4112) {
+                    if n >= 17 {
+                        out.extend([(long << 4) | ((n - 17) >> 8) as u8, (n - 17) as u8]);
+                    } else {
+                        out.push((short << 4) | (n - 2) as u8);
+                    }
+                }
+            }
+            None => {
+                for n in run_chunks(length, min, 513) {
+                    if n >= 18 {
+                        // The length is (high nibble << 4) + next byte + 18
+                        let high = (n - 18).saturating_sub(255).div_ceil(16);
+                        out.extend([(RLE_INSERT_BYTE18 << 4) | high as u8, (n - 18 - 16 * high) as u8, byte]);
+                    } else {
+                        out.extend([(RLE_INSERT_BYTE3 << 4) | (n - 3) as u8, byte]);
+                    }
+                }
+            }
+        }
+        i += length;
+        literal_start = i;
+    }
+    rle_copy(&mut out, &row[literal_start..]);
+    out
+}
+
+// Size of the table of the last positions of 3-byte prefixes
+const RDC_HASH_SIZE: usize = 4096;
+
+fn rdc_hash(bytes: &[u8]) -> usize {
+    let key = ((bytes[0] as usize) << 16) | ((bytes[1] as usize) << 8) | bytes[2] as usize;
+    (key.wrapping_mul(40543) >> 4) % RDC_HASH_SIZE
+}
+
+/// Compress a row with the `SASYZCR2` Ross data compression: groups of 16 items (literal
+/// bytes, runs or copies of a previous pattern) preceded by 16 control bits, big-endian,
+/// set for the items which are not literals
+pub(crate) fn rdc_compress(row: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(row.len() + row.len() / 8 + 2);
+    let mut positions = vec![usize::MAX; RDC_HASH_SIZE];
+    let mut control_position = 0;
+    let mut control_bits = 0u16;
+    let mut items = 16;
+    let mut i = 0;
+    while i < row.len() {
+        if items == 16 {
+            if !out.is_empty() {
+                out[control_position..control_position + 2].copy_from_slice(&control_bits.to_be_bytes());
+            }
+            control_position = out.len();
+            out.extend([0, 0]);
+            control_bits = 0;
+            items = 0;
+        }
+        let bit = 0x8000 >> items;
+        items += 1;
+
+        let length = run_length(&row[i..], 4114);
+        if length >= 3 {
+            if length <= 18 {
+                out.extend([(length - 3) as u8, row[i]]);
+            } else {
+                out.extend([0x10 | ((length - 19) & 0x0F) as u8, ((length - 19) >> 4) as u8, row[i]]);
+            }
+            control_bits |= bit;
+            i += length;
+            continue;
+        }
+
+        if i + 3 <= row.len() {
+            let hash = rdc_hash(&row[i..]);
+            let candidate = positions[hash];
+            positions[hash] = i;
+            // The decompressor copies the pattern at once, it cannot overlap the copy
+            if candidate < i && (3..=4098).contains(&(i - candidate)) {
+                let offset = i - candidate;
+                let length = row[candidate..]
+                    .iter()
+                    .zip(&row[i..])
+                    .take(offset.min(271))
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length >= 3 {
+                    let offset = offset - 3;
+                    if length <= 15 {
+                        out.extend([(length << 4) as u8 | (offset & 0x0F) as u8, (offset >> 4) as u8]);
+                    } else {
+                        out.extend([0x20 | (offset & 0x0F) as u8, (offset >> 4) as u8, (length - 16) as u8]);
+                    }
+                    control_bits |= bit;
+                    i += length;
+                    continue;
+                }
+            }
+        }
+
+        out.push(row[i]);
+        i += 1;
+    }
+    if !row.is_empty() {
+        out[control_position..control_position + 2].copy_from_slice(&control_bits.to_be_bytes());
+    }
+    out
+}
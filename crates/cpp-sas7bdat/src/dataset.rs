@@ -0,0 +1,331 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use polars::prelude::*;
+
+use crate::utilities::get_max_threads;
+use crate::{string_cache_for, SasBatchIterator, SasReadOptions, SasReader};
+
+/// Reads several SAS files with the same layout (e.g. one file per year or per state)
+/// as one dataset.
+///
+/// The schemas are unified by column name: a column missing from a file is read as nulls
+/// and the column types are promoted across the files (integer and decimal columns to
+/// `Float64`, decimals to the widest decimal, `Date` to `Datetime`, datetimes to the finer
+/// time unit and to no time zone when their zones differ, `Categorical` to `String`).
+/// String columns have no length in the schema: longer values in a later file need no
+/// conversion. The paths accept everything `SasReader::with_options` does (compressed
+/// files, archive members, URLs).
+pub struct SasDatasetReader {
+    files: Arc<[String]>,
+    options: SasReadOptions,
+    // Unified schema of the SAS columns
+    data_schema: Schema,
+    source_file_column: Option<PlSmallStr>,
+    parallel: bool,
+}
+
+impl SasDatasetReader {
+    /// Create a reader over `paths`, read in this order. The schema of every file is read.
+    pub fn new<P: AsRef<str>>(paths: &[P], options: SasReadOptions) -> PolarsResult<Self> {
+        if paths.is_empty() {
+            return Err(PolarsError::ComputeError("No SAS file to read".into()));
+        }
+        let files: Vec<String> = paths.iter().map(|path| path.as_ref().to_string()).collect();
+
+        // Unified column types in order of first appearance, and the first file of each column
+        let mut data_schema = Schema::default();
+        let mut origins: PlHashMap<PlSmallStr, usize> = PlHashMap::new();
+        for (index, file) in files.iter().enumerate() {
+            let mut reader = SasReader::with_options(file, options.clone())
+                .map_err(|e| PolarsError::ComputeError(format!("{}: {}", file, e).into()))?;
+            for (name, dtype) in reader.get_schema()?.iter() {
+                let unified = match data_schema.get(name) {
+                    None => dtype.clone(),
+                    Some(current) => promote(current, dtype).ok_or_else(|| PolarsError::SchemaMismatch(
+                        format!(
+                            "Cannot unify the column '{}': {} in {}, {} in {}",
+                            name, current, files[origins[name]], dtype, file
+                        ).into(),
+                    ))?,
+                };
+                origins.entry(name.clone()).or_insert(index);
+                data_schema.with_column(name.clone(), unified);
+            }
+        }
+
+        Ok(SasDatasetReader {
+            files: files.into(),
+            options,
+            data_schema,
+            source_file_column: None,
+            parallel: false,
+        })
+    }
+
+    /// Create a reader over the files matching a glob pattern (e.g. `data/psam_p*.sas7bdat`),
+    /// read in path order
+    pub fn from_glob(pattern: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let mut paths = glob::glob(pattern)
+            .map_err(|e| PolarsError::ComputeError(format!("Invalid glob pattern '{}': {}", pattern, e).into()))?
+            .map(|path| path.map(|path| path.to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PolarsError::ComputeError(format!("{}", e).into()))?;
+        if paths.is_empty() {
+            return Err(PolarsError::ComputeError(format!("No file matches '{}'", pattern).into()));
+        }
+        paths.sort();
+        Self::new(&paths, options)
+    }
+
+    /// Append a `String` column named `name` holding the path of the file of each row
+    pub fn with_source_file_column(mut self, name: &str) -> PolarsResult<Self> {
+        if self.data_schema.contains(name) {
+            return Err(PolarsError::Duplicate(
+                format!("The source file column '{}' is already a column of the SAS files", name).into(),
+            ));
+        }
+        self.source_file_column = Some(name.into());
+        Ok(self)
+    }
+
+    /// Read the files in parallel, `POLARS_MAX_THREADS` at a time. The batches are yielded
+    /// as soon as they are decoded: the batches of different files are interleaved.
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// The files of the dataset
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// The unified schema, with the source file column if any
+    pub fn schema(&self) -> Schema {
+        let mut schema = self.data_schema.clone();
+        if let Some(name) = &self.source_file_column {
+            schema.with_column(name.clone(), DataType::String);
+        }
+        schema
+    }
+
+    /// Stream the batches of all the files, converted to the unified schema
+    pub fn batches(&self) -> PolarsResult<SasDatasetBatchIterator> {
+        let conform = Arc::new(Conform {
+            files: Arc::clone(&self.files),
+            options: self.options.clone(),
+            data_schema: self.data_schema.clone(),
+            source_file_column: self.source_file_column.clone(),
+        });
+        if self.parallel {
+            SasDatasetBatchIterator::parallel(conform)
+        } else {
+            Ok(SasDatasetBatchIterator {
+                _string_cache: string_cache_for(&conform.options),
+                batches: Batches::Sequential { conform, next_file: 0, current: None },
+            })
+        }
+    }
+}
+
+// Reads the files and converts their batches to the unified schema
+struct Conform {
+    files: Arc<[String]>,
+    options: SasReadOptions,
+    data_schema: Schema,
+    source_file_column: Option<PlSmallStr>,
+}
+
+impl Conform {
+    fn open(&self, file_index: usize) -> PolarsResult<SasBatchIterator> {
+        let file = &self.files[file_index];
+        SasBatchIterator::with_options(file, self.options.clone())
+            .map_err(|e| PolarsError::ComputeError(format!("{}: {}", file, e).into()))
+    }
+
+    fn convert(&self, batch: PolarsResult<DataFrame>, file_index: usize) -> PolarsResult<DataFrame> {
+        let file = &self.files[file_index];
+        let batch = batch.map_err(|e| PolarsError::ComputeError(format!("{}: {}", file, e).into()))?;
+        let height = batch.height();
+
+        let mut columns = Vec::with_capacity(self.data_schema.len() + 1);
+        for (name, dtype) in self.data_schema.iter() {
+            let column = match batch.column(name) {
+                Ok(column) if column.dtype() == dtype => column.clone(),
+                Ok(column) => column.cast(dtype)?,
+                Err(_) => Column::full_null(name.clone(), height, dtype),
+            };
+            columns.push(column);
+        }
+        if let Some(name) = &self.source_file_column {
+            columns.push(Column::new_scalar(
+                name.clone(),
+                Scalar::new(DataType::String, AnyValue::StringOwned(file.as_str().into())),
+                height,
+            ));
+        }
+        DataFrame::new(columns)
+    }
+}
+
+/// Streaming iterator over the batches of a `SasDatasetReader`
+pub struct SasDatasetBatchIterator {
+    batches: Batches,
+    // The categories of the files share their physical values
+    _string_cache: Option<polars_core::StringCacheHolder>,
+}
+
+enum Batches {
+    // One file after the other
+    Sequential {
+        conform: Arc<Conform>,
+        next_file: usize,
+        current: Option<(usize, SasBatchIterator)>,
+    },
+    // Files read by worker threads, batches in decoding order
+    Parallel {
+        receiver: Option<Receiver<PolarsResult<DataFrame>>>,
+        workers: Vec<JoinHandle<()>>,
+    },
+}
+
+impl SasDatasetBatchIterator {
+    fn parallel(conform: Arc<Conform>) -> PolarsResult<Self> {
+        let string_cache = string_cache_for(&conform.options);
+        let threads = get_max_threads().clamp(1, conform.files.len());
+        let next_file = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = sync_channel(threads);
+
+        let workers = (0..threads)
+            .map(|_| {
+                let conform = Arc::clone(&conform);
+                let next_file = Arc::clone(&next_file);
+                let sender = sender.clone();
+                thread::Builder::new().name("sas-dataset".to_string()).spawn(move || loop {
+                    let file_index = next_file.fetch_add(1, Ordering::SeqCst);
+                    if file_index >= conform.files.len() {
+                        return;
+                    }
+                    let batches = match conform.open(file_index) {
+                        Ok(batches) => batches,
+                        Err(e) => {
+                            let _ = sender.send(Err(e));
+                            return;
+                        }
+                    };
+                    for batch in batches {
+                        let batch = conform.convert(batch, file_index);
+                        let failed = batch.is_err();
+                        // Stops when the iterator is dropped or after an error
+                        if sender.send(batch).is_err() || failed {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        Ok(SasDatasetBatchIterator {
+            batches: Batches::Parallel { receiver: Some(receiver), workers },
+            _string_cache: string_cache,
+        })
+    }
+}
+
+impl Iterator for SasDatasetBatchIterator {
+    type Item = PolarsResult<DataFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.batches {
+            Batches::Sequential { conform, next_file, current } => loop {
+                if let Some((file_index, batches)) = current {
+                    match batches.next() {
+                        Some(batch) => {
+                            let batch = conform.convert(batch, *file_index);
+                            if batch.is_err() {
+                                // Nothing is yielded after an error
+                                *next_file = conform.files.len();
+                                *current = None;
+                            }
+                            return Some(batch);
+                        }
+                        None => *current = None,
+                    }
+                }
+                if *next_file >= conform.files.len() {
+                    return None;
+                }
+                let file_index = *next_file;
+                *next_file += 1;
+                match conform.open(file_index) {
+                    Ok(batches) => *current = Some((file_index, batches)),
+                    Err(e) => {
+                        *next_file = conform.files.len();
+                        return Some(Err(e));
+                    }
+                }
+            },
+            Batches::Parallel { receiver, .. } => {
+                let batch = receiver.as_ref()?.recv().ok();
+                match batch {
+                    // Nothing is yielded after an error
+                    Some(Err(e)) => {
+                        *receiver = None;
+                        Some(Err(e))
+                    }
+                    Some(batch) => Some(batch),
+                    None => {
+                        *receiver = None;
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SasDatasetBatchIterator {
+    fn drop(&mut self) {
+        if let Batches::Parallel { receiver, workers } = &mut self.batches {
+            // Unblock the workers, then wait for them to release their readers
+            *receiver = None;
+            for worker in workers.drain(..) {
+                let _ = worker.join();
+            }
+        }
+    }
+}
+
+// Type holding the values of both `a` and `b`, None when they cannot be unified
+fn promote(a: &DataType, b: &DataType) -> Option<DataType> {
+    use DataType::*;
+    if a == b {
+        return Some(a.clone());
+    }
+    match (a, b) {
+        (Null, other) | (other, Null) => Some(other.clone()),
+        (Decimal(p1, s1), Decimal(p2, s2)) => {
+            let (p1, s1, p2, s2) = (p1.unwrap_or(38), s1.unwrap_or(0), p2.unwrap_or(38), s2.unwrap_or(0));
+            let scale = s1.max(s2);
+            let precision = (p1.saturating_sub(s1).max(p2.saturating_sub(s2)) + scale).min(38);
+            Some(Decimal(Some(precision), Some(scale)))
+        }
+        (a, b) if a.is_numeric() && b.is_numeric() => Some(Float64),
+        (Date, datetime @ Datetime(..)) | (datetime @ Datetime(..), Date) => Some(datetime.clone()),
+        // The finer unit, without time zone when they differ
+        (Datetime(unit1, zone1), Datetime(unit2, zone2)) => {
+            let unit = match (unit1, unit2) {
+                (TimeUnit::Nanoseconds, _) | (_, TimeUnit::Nanoseconds) => TimeUnit::Nanoseconds,
+                (TimeUnit::Microseconds, _) | (_, TimeUnit::Microseconds) => TimeUnit::Microseconds,
+                _ => TimeUnit::Milliseconds,
+            };
+            Some(Datetime(unit, if zone1 == zone2 { zone1.clone() } else { None }))
+        }
+        (String, Categorical(..)) | (Categorical(..), String) => Some(String),
+        (Categorical(..), Categorical(..)) => Some(a.clone()),
+        _ => None,
+    }
+}
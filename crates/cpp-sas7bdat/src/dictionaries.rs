@@ -0,0 +1,62 @@
+use polars::prelude::*;
+
+use crate::SasReadOptions;
+
+const DICTIONARY_TYPE: DataType = DataType::Categorical(None, CategoricalOrdering::Physical);
+
+// String columns read as `Categorical` because their first batch has few distinct values, see
+// `SasReadOptions::with_dictionary_detection`. The first batch is decoded once, when the
+// schema is read, and kept for the first call to `read_next_batch`.
+pub(crate) struct DictionaryDetection {
+    max_cardinality: u32,
+    // Detected columns, found by `detect`
+    columns: Vec<PlSmallStr>,
+}
+
+impl DictionaryDetection {
+    pub(crate) fn new(options: &SasReadOptions) -> Self {
+        DictionaryDetection {
+            max_cardinality: options.dictionary_max_cardinality.unwrap_or(0),
+            columns: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.max_cardinality == 0
+    }
+
+    pub(crate) fn columns(&self) -> &[PlSmallStr] {
+        &self.columns
+    }
+
+    // Find the string columns of `schema` with at most `max_cardinality` distinct values in
+    // `first_batch` (none when the file has no rows) and change their type in `schema`
+    pub(crate) fn detect(&mut self, first_batch: Option<&DataFrame>, schema: &mut Schema) -> PolarsResult<()> {
+        let Some(first_batch) = first_batch else {
+            return Ok(());
+        };
+        for (name, dtype) in schema.iter() {
+            if *dtype != DataType::String {
+                continue;
+            }
+            let values = first_batch.column(name)?.as_materialized_series().n_unique()?;
+            if values <= self.max_cardinality as usize {
+                self.columns.push(name.clone());
+            }
+        }
+        for name in &self.columns {
+            schema.with_column(name.clone(), DICTIONARY_TYPE);
+        }
+        Ok(())
+    }
+
+    // The categories are registered in the string cache held by the reader, so the
+    // batches share their physical values
+    pub(crate) fn apply(&self, df: &mut DataFrame) -> PolarsResult<()> {
+        for name in &self.columns {
+            let column = df.column(name)?.cast(&DICTIONARY_TYPE)?;
+            df.with_column(column)?;
+        }
+        Ok(())
+    }
+}
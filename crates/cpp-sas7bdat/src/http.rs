@@ -0,0 +1,152 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use crate::options::SasHttpOptions;
+
+/// A remote file read with HTTP `Range` requests.
+///
+/// The file is split in blocks of `block_size` bytes fetched on demand and kept in a
+/// least recently used cache. Once blocks are read in order, the following `read_ahead`
+/// blocks are fetched with the same request. A reader that only parses the header and
+/// the metadata only fetches the blocks holding them.
+pub(crate) struct HttpSource {
+    agent: ureq::Agent,
+    url: String,
+    options: SasHttpOptions,
+    size: u64,
+    position: u64,
+    blocks: HashMap<u64, Vec<u8>>,
+    // Cached block indexes, least recently used first
+    recent: VecDeque<u64>,
+    last_block: Option<u64>,
+}
+
+impl HttpSource {
+    pub(crate) fn open(url: &str, mut options: SasHttpOptions) -> io::Result<Self> {
+        // The fields are public: a block size of 0 is not clamped by `with_block_size`
+        options.block_size = options.block_size.max(1);
+        let mut source = HttpSource {
+            agent: ureq::AgentBuilder::new().build(),
+            url: url.to_string(),
+            options,
+            size: 0,
+            position: 0,
+            blocks: HashMap::new(),
+            recent: VecDeque::new(),
+            last_block: None,
+        };
+        // The first block gives the file size
+        let (data, size) = source.get(0, source.options.block_size)?;
+        source.size = size;
+        source.insert(0, data);
+        source.last_block = Some(0);
+        Ok(source)
+    }
+
+    fn block_count(&self) -> u64 {
+        self.size.div_ceil(self.options.block_size)
+    }
+
+    // Fetch the bytes [start, end) (clipped to the file), returns them with the file size
+    fn get(&self, start: u64, end: u64) -> io::Result<(Vec<u8>, u64)> {
+        let mut request = self.agent.get(&self.url)
+            .set("Range", &format!("bytes={}-{}", start, end - 1));
+        for (name, value) in &self.options.headers {
+            request = request.set(name, value);
+        }
+        let response = match request.call() {
+            Ok(response) => response,
+            // Range starting past the end of an empty file
+            Err(ureq::Error::Status(416, _)) if start == 0 => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(io::Error::new(ErrorKind::Other, format!("{}: {}", self.url, e))),
+        };
+        if response.status() != 206 {
+            return Err(io::Error::new(
+                ErrorKind::Unsupported,
+                format!("{}: the server does not support Range requests (status {})", self.url, response.status()),
+            ));
+        }
+        // Content-Range: bytes <start>-<end>/<size>
+        let size = response.header("Content-Range")
+            .and_then(|range| range.rsplit('/').next())
+            .and_then(|size| size.trim().parse::<u64>().ok())
+            .ok_or_else(|| io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: missing or invalid Content-Range header", self.url),
+            ))?;
+
+        let length = end.min(size).saturating_sub(start);
+        let mut data = Vec::with_capacity(length as usize);
+        response.into_reader().take(length).read_to_end(&mut data)?;
+        if (data.len() as u64) < length {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, format!("{}: truncated response", self.url)));
+        }
+        Ok((data, size))
+    }
+
+    fn insert(&mut self, index: u64, data: Vec<u8>) {
+        if self.blocks.insert(index, data).is_none() {
+            self.recent.push_back(index);
+        }
+        while self.recent.len() > self.options.cache_blocks.max(1) {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.blocks.remove(&oldest);
+            }
+        }
+    }
+
+    // Fetch the block `index` if it is not cached
+    fn load(&mut self, index: u64) -> io::Result<()> {
+        if self.blocks.contains_key(&index) {
+            if let Some(position) = self.recent.iter().position(|&block| block == index) {
+                self.recent.remove(position);
+                self.recent.push_back(index);
+            }
+            return Ok(());
+        }
+
+        let sequential = index > 0 && self.last_block == Some(index - 1);
+        let read_ahead = if sequential { self.options.read_ahead as u64 } else { 0 };
+        // Do not read ahead more than the cache can hold
+        let read_ahead = read_ahead.min(self.options.cache_blocks.saturating_sub(1) as u64);
+        let count = (1 + read_ahead).min(self.block_count() - index);
+
+        let block_size = self.options.block_size;
+        let (data, _) = self.get(index * block_size, (index + count) * block_size)?;
+        for (i, block) in data.chunks(block_size as usize).enumerate() {
+            self.insert(index + i as u64, block.to_vec());
+        }
+        Ok(())
+    }
+}
+
+impl Read for HttpSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+        let block_size = self.options.block_size;
+        let index = self.position / block_size;
+        self.load(index)?;
+        self.last_block = Some(index);
+
+        let block = &self.blocks[&index];
+        let offset = (self.position - index * block_size) as usize;
+        let n = buf.len().min(block.len() - offset);
+        buf[..n].copy_from_slice(&block[offset..offset + n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for HttpSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        };
+        self.position = target.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        Ok(self.position)
+    }
+}
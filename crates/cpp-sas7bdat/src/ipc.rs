@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Arc;
+
+use polars::prelude::*;
+use polars_arrow::datatypes::{ArrowSchema, Metadata};
+use polars_arrow::io::ipc::write::{Compression, FileWriter, StreamWriter, WriteOptions};
+
+use crate::options::format_spec;
+use crate::{SasBatchIterator, SasIpcCompression, SasIpcFormat, SasIpcOptions, SasMetadata};
+
+/// Convert a SAS file to an Arrow IPC file (Feather v2) or stream, one batch at a time.
+///
+/// Every batch read is written as a record batch, `output` `-` writes to the standard
+/// output. The label and format of a column are stored in the metadata of its field under
+/// the `label` and `format` keys, as read by `SasXportWriteOptions::with_field_metadata`.
+/// The dataset metadata is stored in the metadata of the schema under the `sas.` keys
+/// (`sas.dataset_name`, `sas.encoding`, ...). `Categorical` columns are written as strings
+/// in the file format, as dictionaries in the stream format.
+pub fn sas_to_ipc(input: &str, output: &str, options: SasIpcOptions) -> PolarsResult<()> {
+    let mut batches = SasBatchIterator::open(input, options.read_options)?;
+    let mut schema = batches.schema()?.clone();
+    if options.format == SasIpcFormat::File {
+        schema = schema.iter().map(|(name, dtype)| (name.clone(), file_dtype(dtype))).collect();
+    }
+    let metadata = batches.metadata()?;
+    let arrow_schema = Arc::new(arrow_schema(&metadata, &schema, options.compat_level));
+    let schema_metadata: Metadata = metadata
+        .properties()
+        .into_iter()
+        .map(|(key, value)| (format!("sas.{}", key).into(), value.into()))
+        .collect();
+
+    let mut out: BufWriter<Box<dyn Write>> = if output == "-" {
+        BufWriter::new(Box::new(io::stdout().lock()))
+    } else {
+        BufWriter::new(Box::new(File::create(output)?))
+    };
+    let write_options = WriteOptions {
+        compression: match options.compression {
+            SasIpcCompression::None => None,
+            SasIpcCompression::Lz4 => Some(Compression::LZ4),
+            SasIpcCompression::Zstd => Some(Compression::ZSTD),
+        },
+    };
+    let compat_level = options.compat_level;
+
+    match options.format {
+        SasIpcFormat::File => {
+            let mut writer = FileWriter::new(&mut out, arrow_schema, None, write_options);
+            writer.set_custom_schema_metadata(Arc::new(schema_metadata));
+            writer.start()?;
+            for batch in batches {
+                for chunk in categories_as_strings(batch?)?.iter_chunks(compat_level, true) {
+                    writer.write(&chunk, None)?;
+                }
+            }
+            writer.finish()?;
+        }
+        SasIpcFormat::Stream => {
+            let mut writer = StreamWriter::new(&mut out, write_options);
+            writer.set_custom_schema_metadata(Arc::new(schema_metadata));
+            writer.start(&arrow_schema, None)?;
+            for batch in batches {
+                for chunk in batch?.iter_chunks(compat_level, true) {
+                    writer.write(&chunk, None)?;
+                }
+            }
+            writer.finish()?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// The file format holds a single dictionary per field for all the record batches, while the
+// categories of a column grow from batch to batch: they are written as strings
+fn file_dtype(dtype: &DataType) -> DataType {
+    match dtype {
+        DataType::Categorical(..) | DataType::Enum(..) => DataType::String,
+        dtype => dtype.clone(),
+    }
+}
+
+fn categories_as_strings(df: DataFrame) -> PolarsResult<DataFrame> {
+    let columns = df
+        .take_columns()
+        .into_iter()
+        .map(|column| match column.dtype() {
+            DataType::Categorical(..) | DataType::Enum(..) => column.cast(&DataType::String),
+            _ => Ok(column),
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    DataFrame::new(columns)
+}
+
+// The Arrow schema of the columns read, with the label and format of the SAS columns
+fn arrow_schema(metadata: &SasMetadata, schema: &Schema, compat_level: CompatLevel) -> ArrowSchema {
+    let fields = schema.iter_fields().map(|field| {
+        let field = field.to_arrow(compat_level);
+        let Some(column) = metadata.columns.iter().find(|column| column.name == field.name.as_str()) else {
+            return field;
+        };
+        let mut field_metadata = Metadata::new();
+        if !column.label.is_empty() {
+            field_metadata.insert("label".into(), column.label.as_str().into());
+        }
+        if let Some(format) = format_spec(column) {
+            field_metadata.insert("format".into(), format.into());
+        }
+        if field_metadata.is_empty() {
+            field
+        } else {
+            field.with_metadata(field_metadata)
+        }
+    });
+    fields.collect()
+}
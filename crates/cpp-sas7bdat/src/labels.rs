@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use polars::prelude::*;
+
+use crate::{SasMetadata, SasRange, SasReadOptions, SasValueLabelMode, SasValueLabels};
+
+const LABEL_TYPE: DataType = DataType::Categorical(None, CategoricalOrdering::Physical);
+
+// Value labels applied to the columns of a reader, see `SasReadOptions::with_catalog`
+pub(crate) struct ValueLabels {
+    formats: HashMap<String, SasValueLabels>,
+    mode: SasValueLabelMode,
+    // Labelled columns, the labels of their format and whether their missing values are
+    // NaNs tagged with their special missing value, found by `bind`
+    columns: Vec<(PlSmallStr, SasValueLabels, bool)>,
+}
+
+impl ValueLabels {
+    pub(crate) fn new(options: &SasReadOptions) -> Self {
+        ValueLabels {
+            formats: options.value_labels.clone(),
+            mode: options.value_label_mode,
+            columns: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+
+    // Find the columns whose format has labels and change their type in `schema`. Character
+    // formats (`$` first) only apply to string columns, numeric ones to the other columns.
+    pub(crate) fn bind(&mut self, metadata: &SasMetadata, schema: &mut Schema) -> PolarsResult<()> {
+        for column in &metadata.columns {
+            let Some(labels) = self.formats.get(&column.format.to_ascii_uppercase()) else {
+                continue;
+            };
+            let Some(dtype) = schema.get(column.name.as_str()) else {
+                continue;
+            };
+            if is_character(dtype) != column.format.starts_with('$') {
+                continue;
+            }
+            let tagged = *dtype == DataType::Float64 && labels_special_missing(labels);
+            self.columns.push((column.name.as_str().into(), labels.clone(), tagged));
+        }
+        for (name, ..) in &self.columns {
+            match self.mode {
+                SasValueLabelMode::Replace => {
+                    schema.with_column(name.clone(), LABEL_TYPE);
+                }
+                SasValueLabelMode::Companion => {
+                    let label_name = label_name(name);
+                    if schema.contains(&label_name) {
+                        return Err(PolarsError::Duplicate(
+                            format!("The label column '{}' is already a column of the file", label_name).into(),
+                        ));
+                    }
+                    let index = schema.index_of(name).unwrap();
+                    schema.insert_at_index(index + 1, label_name.into(), LABEL_TYPE)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply(&self, df: &mut DataFrame) -> PolarsResult<()> {
+        for (name, labels, tagged) in &self.columns {
+            let labelled = label(df.column(name)?, labels)?;
+            match self.mode {
+                SasValueLabelMode::Replace => {
+                    df.with_column(labelled)?;
+                }
+                SasValueLabelMode::Companion => {
+                    if *tagged {
+                        // The values keep their missing values as nulls
+                        let values = df.column(name)?.as_materialized_series().f64()?;
+                        let values = Float64Chunked::from_iter_options(
+                            name.clone(),
+                            values.iter().map(|value| value.filter(|value| !value.is_nan())),
+                        );
+                        df.with_column(values.into_column())?;
+                    }
+                    let index = df.get_column_index(name).unwrap();
+                    df.insert_column(index + 1, labelled.with_name(label_name(name).into()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Whether the labels of a numeric format label a special missing value `.A`-`.Z` or `._`
+fn labels_special_missing(labels: &SasValueLabels) -> bool {
+    labels.iter().any(|(range, _)| matches!(range, SasRange::Missing(tag) if *tag != '.'))
+}
+
+// The numeric formats labelling special missing values, upper case: their columns are read
+// as `Float64`, with their missing values as NaNs tagged as SAS stores them
+pub(crate) fn tagged_missing_formats(options: &SasReadOptions) -> Vec<String> {
+    options
+        .value_labels
+        .iter()
+        .filter(|(format, labels)| !format.starts_with('$') && labels_special_missing(labels))
+        .map(|(format, _)| format.clone())
+        .collect()
+}
+
+// The missing value `.<tag>` as SAS stores it: a NaN with the complement of the tag code in
+// its fifth byte, 0 for `._`, 1 for `.`, then 2 for `.A` to 27 for `.Z`
+pub(crate) fn tagged_missing(tag: char) -> f64 {
+    let code = match tag {
+        '_' => 0,
+        'A'..='Z' => tag as u8 - b'A' + 2,
+        _ => 1,
+    };
+    f64::from_bits(0xffff_0000_0000_0000 | ((!code as u64) << 40))
+}
+
+// The tag of a missing value stored as a NaN, see `tagged_missing`; some writers store the
+// character of the tag instead of its code
+fn missing_tag(value: f64) -> char {
+    match !((value.to_bits() >> 40) as u8) {
+        0 => '_',
+        code @ 2..=27 => (b'A' + code - 2) as char,
+        code @ (b'A'..=b'Z' | b'_') => code as char,
+        _ => '.',
+    }
+}
+
+fn label_name(name: &str) -> String {
+    format!("{}_label", name)
+}
+
+fn is_character(dtype: &DataType) -> bool {
+    matches!(dtype, DataType::String | DataType::Categorical(..))
+}
+
+// The labels of the values of `column`, as a `Categorical` of the same name
+fn label(column: &Column, labels: &SasValueLabels) -> PolarsResult<Column> {
+    let name = column.name().clone();
+    let labelled = if is_character(column.dtype()) {
+        let values = column.cast(&DataType::String)?;
+        let values = values.as_materialized_series().str()?;
+        StringChunked::from_iter_options(name, values.iter().map(|value| string_label(value, labels)))
+    } else {
+        let values = column.cast(&DataType::Float64)?;
+        let values = values.as_materialized_series().f64()?;
+        StringChunked::from_iter_options(name, values.iter().map(|value| number_label(value, labels)))
+    };
+    Ok(labelled.into_series().cast(&LABEL_TYPE)?.into_column())
+}
+
+// The label of the first range holding `value`, then of `OTHER`
+fn find_label(labels: &SasValueLabels, holds: impl Fn(&SasRange) -> bool) -> Option<&str> {
+    labels
+        .iter()
+        .find(|(range, _)| holds(range))
+        .or_else(|| labels.iter().find(|(range, _)| *range == SasRange::Other))
+        .map(|(_, label)| label.as_str())
+}
+
+// Missing values are nulls, labelled by `.`, or NaNs labelled by their special missing value
+fn number_label(value: Option<f64>, labels: &SasValueLabels) -> Option<Cow<'_, str>> {
+    let missing = match value {
+        None => Some('.'),
+        Some(value) if value.is_nan() => Some(missing_tag(value)),
+        Some(_) => None,
+    };
+    let value = value.filter(|value| !value.is_nan());
+    let label = find_label(labels, |range| match (range, value, missing) {
+        (SasRange::Numbers(low, high), Some(value), _) => {
+            low.is_none_or(|low| value >= low) && high.is_none_or(|high| value <= high)
+        }
+        (SasRange::Missing(tag), _, Some(missing)) => *tag == missing,
+        _ => false,
+    });
+    match (label, value) {
+        (Some(label), _) => Some(Cow::Borrowed(label)),
+        (None, Some(value)) => Some(Cow::Owned(value.to_string())),
+        (None, None) => None,
+    }
+}
+
+// Values are compared without their trailing blanks, a missing value is blank
+fn string_label<'a>(value: Option<&'a str>, labels: &'a SasValueLabels) -> Option<Cow<'a, str>> {
+    let text = value.unwrap_or_default().trim_end_matches(' ');
+    let label = find_label(labels, |range| match range {
+        SasRange::Strings(low, high) => {
+            low.as_deref().is_none_or(|low| text >= low) && high.as_deref().is_none_or(|high| text <= high)
+        }
+        _ => false,
+    });
+    label.or(value).map(Cow::Borrowed)
+}
@@ -1,8 +1,59 @@
 use std::ffi::{CStr, CString};
-use std::os::raw::{c_char};
+use std::ops::Range;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::os::raw::{c_char, c_void};
+use std::path::Path;
 use std::ptr;
+use std::sync::Arc;
 use polars::prelude::*;
 use polars_arrow;
+use memmap2::Mmap;
+
+mod archive;
+mod catalog;
+mod compression;
+mod compressors;
+mod dataset;
+mod dictionaries;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "ipc")]
+mod ipc;
+mod labels;
+mod options;
+mod parallel;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod prefetch;
+mod put;
+mod source;
+mod utilities;
+mod writer;
+mod xport;
+
+pub use archive::SasArchiveMember;
+pub use catalog::{SasCatalog, SasRange, SasValueLabels};
+pub use dataset::{SasDatasetBatchIterator, SasDatasetReader};
+#[cfg(feature = "ipc")]
+pub use ipc::sas_to_ipc;
+pub use parallel::SasParallelBatchIterator;
+#[cfg(feature = "parquet")]
+pub use parquet::sas_to_parquet;
+pub use prefetch::SasPrefetchIterator;
+pub use put::sas_put;
+pub use writer::SasWriter;
+pub use xport::write_xpt;
+pub use options::{
+    SasCompression, SasErrorPolicy, SasReadOptions, SasRowIndex, SasRowIndexType, SasStringLayout, SasTemporalType,
+    SasValueLabelMode, SasWriteOptions, SasXportVersion, SasXportWriteOptions,
+};
+#[cfg(feature = "http")]
+pub use options::SasHttpOptions;
+#[cfg(feature = "ipc")]
+pub use options::{SasIpcCompression, SasIpcFormat, SasIpcOptions};
+#[cfg(feature = "parquet")]
+pub use options::SasParquetOptions;
 
 // Error codes matching your C++ header exactly
 #[repr(C)]
@@ -36,6 +87,220 @@ pub struct SasArrowColumnInfo {
     pub index: u32,
 }
 
+// Temporal type enum matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SasArrowTemporalType {
+    SasArrowTemporalNone = 0,
+    SasArrowTemporalDate = 1,
+    SasArrowTemporalDatetime = 2,
+    SasArrowTemporalTime = 3,
+}
+
+// Format mapping structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowFormatMapping {
+    pub format_name: *const c_char,
+    pub temporal_type: SasArrowTemporalType,
+}
+
+// String layout enum matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SasArrowStringLayout {
+    SasArrowStringUtf8 = 0,
+    SasArrowStringUtf8View = 1,
+    SasArrowStringBinaryView = 2,
+}
+
+// Error policy enum matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SasArrowErrorPolicy {
+    SasArrowOnErrorAbort = 0,
+    SasArrowOnErrorNull = 1,
+}
+
+// Diagnostic structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowDiagnostic {
+    pub row: u64,
+    pub column_index: u32,
+    pub column_name: *const c_char,
+    pub message: *const c_char,
+}
+
+// Batch position structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SasArrowBatchPosition {
+    pub first_row: u64,
+    pub first_page: u64,
+    pub last_page: u64,
+}
+
+// Column metadata structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowColumnMetadata {
+    pub name: *const c_char,
+    pub label: *const c_char,
+    pub format: *const c_char,
+    pub format_width: u32,
+    pub format_decimals: u32,
+    pub length: u32,
+}
+
+// Dataset metadata structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowDatasetMetadata {
+    pub dataset_name: *const c_char,
+    pub encoding: *const c_char,
+    pub sas_release: *const c_char,
+    pub os_name: *const c_char,
+    pub row_count: u64,
+}
+
+// Callback data source structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowDataSource {
+    pub user_data: *mut c_void,
+    pub read: Option<unsafe extern "C" fn(*mut c_void, *mut c_void, u64) -> i64>,
+    pub seek: Option<unsafe extern "C" fn(*mut c_void, u64) -> i32>,
+    pub eof: Option<unsafe extern "C" fn(*mut c_void) -> bool>,
+    pub release: Option<unsafe extern "C" fn(*mut c_void)>,
+}
+
+// Read options structure matching your C++ header
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SasArrowReadOptions {
+    pub chunk_size: u32,
+    pub detect_temporal: bool,
+    pub format_mappings: *const SasArrowFormatMapping,
+    pub num_format_mappings: u32,
+    pub fixed_decimal: bool,
+    pub dictionary_columns: *const *const c_char,
+    pub num_dictionary_columns: u32,
+    pub string_layout: SasArrowStringLayout,
+    pub on_error: SasArrowErrorPolicy,
+    pub tagged_missing_formats: *const *const c_char,
+    pub num_tagged_missing_formats: u32,
+}
+
+impl From<SasTemporalType> for SasArrowTemporalType {
+    fn from(temporal_type: SasTemporalType) -> Self {
+        match temporal_type {
+            SasTemporalType::Number => SasArrowTemporalType::SasArrowTemporalNone,
+            SasTemporalType::Date => SasArrowTemporalType::SasArrowTemporalDate,
+            SasTemporalType::Datetime => SasArrowTemporalType::SasArrowTemporalDatetime,
+            SasTemporalType::Time => SasArrowTemporalType::SasArrowTemporalTime,
+        }
+    }
+}
+
+impl From<SasStringLayout> for SasArrowStringLayout {
+    fn from(string_layout: SasStringLayout) -> Self {
+        match string_layout {
+            SasStringLayout::Utf8 => SasArrowStringLayout::SasArrowStringUtf8,
+            SasStringLayout::Utf8View => SasArrowStringLayout::SasArrowStringUtf8View,
+            SasStringLayout::BinaryView => SasArrowStringLayout::SasArrowStringBinaryView,
+        }
+    }
+}
+
+impl From<SasErrorPolicy> for SasArrowErrorPolicy {
+    fn from(policy: SasErrorPolicy) -> Self {
+        match policy {
+            SasErrorPolicy::Abort => SasArrowErrorPolicy::SasArrowOnErrorAbort,
+            SasErrorPolicy::Null => SasArrowErrorPolicy::SasArrowOnErrorNull,
+        }
+    }
+}
+
+/// Format of the file read by a `SasReader`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasFileFormat {
+    Sas7bdat,
+    /// SAS transport file, version 5
+    XportV5,
+    /// SAS transport file, version 8/9 (long names and labels)
+    XportV8,
+}
+
+/// SAS metadata of a column
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SasColumnMetadata {
+    pub name: String,
+    pub label: String,
+    /// Format name without width and decimals, e.g. `DATE`
+    pub format: String,
+    /// `w` in `FORMATw.d`, 0 if not set
+    pub format_width: u32,
+    /// `d` in `FORMATw.d`
+    pub format_decimals: u32,
+    /// Length of the stored values in bytes
+    pub length: u32,
+}
+
+/// SAS metadata of a dataset
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SasMetadata {
+    pub dataset_name: String,
+    /// Dataset label, only read from XPORT files
+    pub label: String,
+    pub encoding: String,
+    pub sas_release: String,
+    pub os_name: String,
+    /// Number of rows, None when the file does not store it (XPORT)
+    pub row_count: Option<u64>,
+    pub columns: Vec<SasColumnMetadata>,
+}
+
+impl SasMetadata {
+    // The dataset properties set in the file, as key-value pairs for the metadata of exports
+    #[cfg(any(feature = "parquet", feature = "ipc"))]
+    pub(crate) fn properties(&self) -> Vec<(&'static str, String)> {
+        let mut properties = vec![
+            ("dataset_name", self.dataset_name.clone()),
+            ("dataset_label", self.label.clone()),
+            ("encoding", self.encoding.clone()),
+            ("sas_release", self.sas_release.clone()),
+            ("os_name", self.os_name.clone()),
+        ];
+        properties.retain(|(_, value)| !value.is_empty());
+        if let Some(row_count) = self.row_count {
+            properties.push(("row_count", row_count.to_string()));
+        }
+        properties
+    }
+}
+
+/// A batch with the physical location of its rows, to trace a value back to the file
+#[derive(Debug, Clone)]
+pub struct SasBatch {
+    pub df: DataFrame,
+    /// Index of the first row of the batch in the file, from 0
+    pub first_row: u64,
+    /// Pages holding the rows of the batch, numbered from 0 after the header. For an
+    /// XPORT file, the 80-byte records holding them, numbered from the start of the file.
+    pub page_range: Range<u64>,
+}
+
+/// A value that could not be read and was replaced by a null
+#[derive(Debug, Clone, PartialEq)]
+pub struct SasDiagnostic {
+    /// Row index in the dataset
+    pub row: u64,
+    pub column_index: u32,
+    pub column_name: String,
+    pub message: String,
+}
+
 // Arrow FFI structures (compatible with Arrow C Data Interface)
 #[repr(C)]
 pub struct CArrowSchema {
@@ -112,6 +377,27 @@ extern "C" {
         reader_out: *mut *mut SasArrowReader,
     ) -> SasArrowErrorCode;
 
+    fn sas_arrow_reader_with_options(
+        file_path: *const c_char,
+        options: *const SasArrowReadOptions,
+        reader_out: *mut *mut SasArrowReader,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_from_memory(
+        data: *const u8,
+        length: u64,
+        user_data: *mut c_void,
+        release: Option<unsafe extern "C" fn(*mut c_void)>,
+        options: *const SasArrowReadOptions,
+        reader_out: *mut *mut SasArrowReader,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_from_source(
+        source: *const SasArrowDataSource,
+        options: *const SasArrowReadOptions,
+        reader_out: *mut *mut SasArrowReader,
+    ) -> SasArrowErrorCode;
+
     fn sas_arrow_reader_get_info(
         reader: *const SasArrowReader,
         info: *mut SasArrowReaderInfo,
@@ -137,6 +423,45 @@ extern "C" {
 
     fn sas_arrow_reader_destroy(reader: *mut SasArrowReader);
 
+    fn sas_arrow_reader_get_diagnostics(
+        reader: *mut SasArrowReader,
+        diagnostics_out: *mut *const SasArrowDiagnostic,
+        num_diagnostics: *mut u32,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_get_column_metadata(
+        reader: *const SasArrowReader,
+        column_index: u32,
+        metadata_out: *mut SasArrowColumnMetadata,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_get_dataset_metadata(
+        reader: *const SasArrowReader,
+        metadata_out: *mut SasArrowDatasetMetadata,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_format_temporal_type(
+        format_name: *const c_char,
+        options: *const SasArrowReadOptions,
+    ) -> SasArrowTemporalType;
+
+    fn sas_arrow_reader_get_batch_position(
+        reader: *mut SasArrowReader,
+        position_out: *mut SasArrowBatchPosition,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_get_page_range(
+        reader: *mut SasArrowReader,
+        first_data_page: *mut u64,
+        page_count: *mut u64,
+    ) -> SasArrowErrorCode;
+
+    fn sas_arrow_reader_set_page_range(
+        reader: *mut SasArrowReader,
+        first_page: u64,
+        end_page: u64,
+    ) -> SasArrowErrorCode;
+
     fn sas_arrow_get_last_error() -> *const c_char;
 
     fn sas_arrow_error_message(error_code: SasArrowErrorCode) -> *const c_char;
@@ -144,26 +469,280 @@ extern "C" {
     fn sas_arrow_is_ok(error_code: SasArrowErrorCode) -> bool;
 }
 
+// Hold the global string cache when columns may be read as `Categorical`: the categories of
+// all the batches share their physical values while it is held
+pub(crate) fn string_cache_for(options: &SasReadOptions) -> Option<polars_core::StringCacheHolder> {
+    options.uses_dictionaries().then(polars_core::StringCacheHolder::hold)
+}
+
 pub struct SasReader {
+    // Null when reading an XPORT file
     reader: *mut SasArrowReader,
+    xport: Option<Box<xport::XportReader>>,
     info: SasArrowReaderInfo,
     cached_schema: Option<Schema>,
     cached_arrow_field: Option<polars_arrow::datatypes::Field>,
+    row_index: Option<SasRowIndex>,
+    // Rows read, from `skip_rows` to `skip_rows + n_rows`
+    rows: Range<u64>,
+    // Index of the row after the last batch decoded
+    next_row: u64,
+    dictionaries: dictionaries::DictionaryDetection,
+    // First batch, decoded by `get_schema` to detect the dictionary columns
+    first_batch: Option<(DataFrame, u64, Range<u64>)>,
+    value_labels: labels::ValueLabels,
+    formatted_columns: put::FormattedColumns,
+    _string_cache: Option<polars_core::StringCacheHolder>,
 }
 
 impl SasReader {
     /// Create a new SAS reader
     pub fn new(file_path: &str, chunk_size: Option<u32>) -> PolarsResult<Self> {
+        Self::with_options(file_path, SasReadOptions { chunk_size, ..Default::default() })
+    }
+
+    /// Create a new reader for a `.sas7bdat` or a SAS transport (XPORT, `.xpt`) file, the
+    /// format being detected from the header magic, see `file_format`. Same as `with_options`.
+    pub fn open(file_path: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        Self::with_options(file_path, options)
+    }
+
+    /// Create a new SAS reader with explicit read options.
+    /// Files compressed with gzip, zstd, bzip2 or xz (detected from their magic bytes or
+    /// extension) are decompressed while read when the matching cargo feature is enabled.
+    /// A path going through a ZIP or TAR archive (`vendor.zip/dir/member.sas7bdat`) reads
+    /// the member, see `open_archive_member`. With the `http` feature, `http://` and
+    /// `https://` URLs are read with `from_url` and the default HTTP options. Transport
+    /// (XPORT) files are detected in all these cases.
+    pub fn with_options(file_path: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        #[cfg(feature = "http")]
+        if file_path.starts_with("http://") || file_path.starts_with("https://") {
+            return Self::from_url(file_path, SasHttpOptions::default(), options);
+        }
+        if let Some((archive_path, member_name)) = archive::split_path(Path::new(file_path)) {
+            return Self::open_archive_member(&archive_path.to_string_lossy(), &member_name, options);
+        }
+        if let Some(codec) = compression::detect(Path::new(file_path)) {
+            let source = compression::Decompressed::open(Path::new(file_path), codec)?;
+            return Self::from_reader(source, options);
+        }
+        if let Ok(mut file) = File::open(file_path) {
+            let mut prefix = [0u8; xport::MAGIC.len()];
+            let length = compression::read_prefix(&mut file, &mut prefix)?;
+            if prefix[..length].starts_with(xport::MAGIC) {
+                file.rewind()?;
+                return Self::from_xport(Box::new(file), options);
+            }
+        }
+        if options.mmap {
+            return Self::from_mmap(file_path, options);
+        }
+        
         let c_path = CString::new(file_path)
             .map_err(|e| PolarsError::ComputeError(format!("Invalid file path: {}", e).into()))?;
         
-        let mut reader: *mut SasArrowReader = ptr::null_mut();
-        let chunk_size = chunk_size.unwrap_or(0); // 0 = default (65536)
+        Self::create(&options, |c_options, reader| unsafe {
+            sas_arrow_reader_with_options(c_path.as_ptr(), c_options, reader)
+        })
+    }
+
+    // Map the file and read it as an in-memory buffer
+    fn from_mmap(file_path: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let file = File::open(file_path)?;
+        // SAFETY: the mapping is only read; as documented by `with_mmap`, the file must not
+        // be modified while the reader is alive
+        let mmap = unsafe { Mmap::map(&file)? };
+        #[cfg(unix)]
+        if options.sequential_access {
+            mmap.advise(memmap2::Advice::Sequential)?;
+        }
         
-        let result = unsafe {
-            sas_arrow_reader(c_path.as_ptr(), chunk_size, &mut reader)
+        Self::from_memory(mmap, options)
+    }
+
+    /// Create a new SAS reader over the member `member_name` of a ZIP or TAR archive.
+    /// The member is decompressed while read, nothing is extracted to disk. ZIP archives
+    /// need the `zip` feature, TAR archives the `tar` feature (and the codec feature for a
+    /// compressed `.tar.gz`, `.tar.zst`, ...).
+    pub fn open_archive_member(archive_path: &str, member_name: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let source = archive::open_member(Path::new(archive_path), member_name)?;
+        Self::from_reader(source, options)
+    }
+
+    /// Create a new SAS reader over a remote file, fetched on demand with HTTP `Range` requests.
+    /// Reading the schema only fetches the blocks holding the header and the metadata.
+    #[cfg(feature = "http")]
+    pub fn from_url(url: &str, http_options: SasHttpOptions, options: SasReadOptions) -> PolarsResult<Self> {
+        let source = http::HttpSource::open(url, http_options)?;
+        Self::from_reader(source, options)
+    }
+
+    /// List the SAS datasets (`.sas7bdat` members) of a ZIP or TAR archive
+    pub fn list_archive_members(archive_path: &str) -> PolarsResult<Vec<SasArchiveMember>> {
+        Ok(archive::list_members(Path::new(archive_path))?)
+    }
+
+    /// SAS metadata (dataset name, row count, columns) of the member `member_name` of a ZIP
+    /// or TAR archive, read from its header and metadata pages without reading any row
+    pub fn read_archive_member_metadata(archive_path: &str, member_name: &str) -> PolarsResult<SasMetadata> {
+        let options = SasReadOptions::new().with_chunk_size(1);
+        Self::open_archive_member(archive_path, member_name, options)?.metadata()
+    }
+
+    /// Create a new SAS reader from any seekable byte stream (in-memory buffer, archive
+    /// member, decrypted stream, ...). The SAS data starts at the current stream position.
+    /// Transport (XPORT) files are detected and read as with `open`.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(mut source: R, options: SasReadOptions) -> PolarsResult<Self> {
+        let start = source.stream_position()?;
+        let mut prefix = [0u8; xport::MAGIC.len()];
+        let length = compression::read_prefix(&mut source, &mut prefix)?;
+        source.seek(SeekFrom::Start(start))?;
+        if prefix[..length].starts_with(xport::MAGIC) {
+            return Self::from_xport(Box::new(source), options);
+        }
+        
+        Self::create(&options, move |c_options, reader| {
+            let data_source = source::callback_source(source, start);
+            unsafe { sas_arrow_reader_from_source(&data_source, c_options, reader) }
+        })
+    }
+
+    // Read the first member of a transport file. The chunk size, the temporal formats, the
+    // row index, the skipped rows, the limit, the dictionary detection, the value labels and
+    // the formatted columns apply, the other options only concern `.sas7bdat` files.
+    fn from_xport(source: Box<dyn archive::ReadSeek>, options: SasReadOptions) -> PolarsResult<Self> {
+        let xport = Self::with_c_options(&options, |c_options| {
+            xport::XportReader::open(source, &options, |format| {
+                let Ok(c_format) = CString::new(format) else {
+                    return SasTemporalType::Number;
+                };
+                match unsafe { sas_arrow_format_temporal_type(c_format.as_ptr(), c_options) } {
+                    SasArrowTemporalType::SasArrowTemporalDate => SasTemporalType::Date,
+                    SasArrowTemporalType::SasArrowTemporalDatetime => SasTemporalType::Datetime,
+                    SasArrowTemporalType::SasArrowTemporalTime => SasTemporalType::Time,
+                    SasArrowTemporalType::SasArrowTemporalNone => SasTemporalType::Number,
+                }
+            })
+        })??;
+        
+        Ok(SasReader {
+            reader: ptr::null_mut(),
+            info: SasArrowReaderInfo {
+                num_columns: xport.schema().len() as u32,
+                chunk_size: xport.chunk_size() as u32,
+                schema_ready: true,
+            },
+            xport: Some(Box::new(xport)),
+            cached_schema: None,
+            cached_arrow_field: None,
+            row_index: options.row_index.clone(),
+            rows: options.rows(),
+            next_row: 0,
+            dictionaries: dictionaries::DictionaryDetection::new(&options),
+            first_batch: None,
+            value_labels: labels::ValueLabels::new(&options),
+            formatted_columns: put::FormattedColumns::new(&options),
+            _string_cache: string_cache_for(&options),
+        })
+    }
+
+    /// Create a new SAS reader over a SAS file held in memory. The pages are decoded
+    /// directly from the buffer, without copy.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, options: SasReadOptions) -> PolarsResult<Self> {
+        Self::from_memory(bytes.into(), options)
+    }
+
+    /// Create a new SAS reader over a `bytes::Bytes` buffer, without copy
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(bytes: bytes::Bytes, options: SasReadOptions) -> PolarsResult<Self> {
+        Self::from_memory(bytes, options)
+    }
+
+    // The C++ reader keeps `owner` alive and reads its bytes in place
+    fn from_memory<T: AsRef<[u8]> + Send + 'static>(owner: T, options: SasReadOptions) -> PolarsResult<Self> {
+        unsafe extern "C" fn release<T>(user_data: *mut c_void) {
+            drop(Box::from_raw(user_data as *mut T));
+        }
+        
+        if owner.as_ref().starts_with(xport::MAGIC) {
+            return Self::from_xport(Box::new(Cursor::new(owner)), options);
+        }
+        
+        Self::create(&options, move |c_options, reader| {
+            // The heap data of T does not move when T itself is boxed
+            let owner = Box::new(owner);
+            let data: &[u8] = (*owner).as_ref();
+            let (data_ptr, data_len) = (data.as_ptr(), data.len() as u64);
+            unsafe {
+                sas_arrow_reader_from_memory(
+                    data_ptr,
+                    data_len,
+                    Box::into_raw(owner) as *mut c_void,
+                    Some(release::<T>),
+                    c_options,
+                    reader,
+                )
+            }
+        })
+    }
+
+    /// Convert the read options for the C++ code and call `f` with them
+    fn with_c_options<T>(options: &SasReadOptions, f: impl FnOnce(&SasArrowReadOptions) -> T) -> PolarsResult<T> {
+        // Keep the format names alive until `f` returns
+        let c_format_names = options.temporal_formats.iter()
+            .map(|(name, _)| CString::new(name.as_str())
+                .map_err(|e| PolarsError::ComputeError(format!("Invalid format name: {}", e).into())))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let format_mappings: Vec<SasArrowFormatMapping> = c_format_names.iter()
+            .zip(options.temporal_formats.iter())
+            .map(|(c_name, (_, temporal_type))| SasArrowFormatMapping {
+                format_name: c_name.as_ptr(),
+                temporal_type: (*temporal_type).into(),
+            })
+            .collect();
+        
+        let c_dictionary_names = options.dictionary_columns.iter()
+            .map(|name| CString::new(name.as_str())
+                .map_err(|e| PolarsError::ComputeError(format!("Invalid column name: {}", e).into())))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let dictionary_columns: Vec<*const c_char> = c_dictionary_names.iter()
+            .map(|c_name| c_name.as_ptr())
+            .collect();
+        
+        let c_tagged_formats = labels::tagged_missing_formats(options).into_iter()
+            .map(|format| CString::new(format)
+                .map_err(|e| PolarsError::ComputeError(format!("Invalid format name: {}", e).into())))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let tagged_missing_formats: Vec<*const c_char> = c_tagged_formats.iter()
+            .map(|c_format| c_format.as_ptr())
+            .collect();
+        
+        let c_options = SasArrowReadOptions {
+            chunk_size: options.chunk_size.unwrap_or(0), // 0 = default (65536)
+            detect_temporal: options.detect_temporal,
+            format_mappings: format_mappings.as_ptr(),
+            num_format_mappings: format_mappings.len() as u32,
+            fixed_decimal: options.fixed_decimal,
+            dictionary_columns: dictionary_columns.as_ptr(),
+            num_dictionary_columns: dictionary_columns.len() as u32,
+            string_layout: options.string_layout.into(),
+            on_error: options.on_error.into(),
+            tagged_missing_formats: tagged_missing_formats.as_ptr(),
+            num_tagged_missing_formats: tagged_missing_formats.len() as u32,
         };
         
+        Ok(f(&c_options))
+    }
+
+    /// Convert the read options and build the C++ reader with `create`
+    fn create(
+        options: &SasReadOptions,
+        create: impl FnOnce(&SasArrowReadOptions, *mut *mut SasArrowReader) -> SasArrowErrorCode,
+    ) -> PolarsResult<Self> {
+        let mut reader: *mut SasArrowReader = ptr::null_mut();
+        
+        let result = Self::with_c_options(options, |c_options| create(c_options, &mut reader))?;
+        
         if result != SasArrowErrorCode::SasArrowOk {
             return Err(Self::error_from_code(result));
         }
@@ -186,44 +765,102 @@ impl SasReader {
         
         Ok(SasReader { 
             reader, 
+            xport: None,
             info,
             cached_schema: None,
             cached_arrow_field: None,
+            row_index: options.row_index.clone(),
+            rows: options.rows(),
+            next_row: 0,
+            dictionaries: dictionaries::DictionaryDetection::new(options),
+            first_batch: None,
+            value_labels: labels::ValueLabels::new(options),
+            formatted_columns: put::FormattedColumns::new(options),
+            _string_cache: string_cache_for(options),
         })
     }
     
     /// Get schema information
     pub fn get_schema(&mut self) -> PolarsResult<&Schema> {
         if self.cached_schema.is_none() {
-            let mut c_schema = CArrowSchema::empty();
-            
-            let result = unsafe {
-                sas_arrow_reader_get_schema(self.reader, &mut c_schema)
+            let mut polars_schema = match &self.xport {
+                Some(xport) => xport.schema().clone(),
+                None => {
+                    let mut c_schema = CArrowSchema::empty();
+                    
+                    let result = unsafe {
+                        sas_arrow_reader_get_schema(self.reader, &mut c_schema)
+                    };
+                    
+                    if result != SasArrowErrorCode::SasArrowOk {
+                        return Err(Self::error_from_code(result));
+                    }
+                    
+                    // Convert and cache both schemas
+                    let (polars_schema, arrow_field) = unsafe { 
+                        self.arrow_schema_to_polars_schema(&c_schema)? 
+                    };
+                    self.cached_arrow_field = Some(arrow_field);
+                    polars_schema
+                }
             };
             
-            if result != SasArrowErrorCode::SasArrowOk {
-                return Err(Self::error_from_code(result));
+            if !self.dictionaries.is_empty() {
+                self.first_batch = self.read_data_batch()?;
+                let first_batch = self.first_batch.as_ref().map(|(df, ..)| df);
+                self.dictionaries.detect(first_batch, &mut polars_schema)?;
             }
             
-            // Convert and cache both schemas
-            let (polars_schema, arrow_field) = unsafe { 
-                self.arrow_schema_to_polars_schema(&c_schema)? 
-            };
+            if !self.formatted_columns.is_empty() || !self.value_labels.is_empty() {
+                let metadata = self.metadata()?;
+                self.formatted_columns.bind(&metadata, &mut polars_schema)?;
+                self.value_labels.bind(&metadata, &mut polars_schema)?;
+            }
+            
+            if let Some(row_index) = &self.row_index {
+                if polars_schema.contains(&row_index.name) {
+                    return Err(PolarsError::Duplicate(
+                        format!("The row index '{}' is already a column of the file", row_index.name).into(),
+                    ));
+                }
+                polars_schema.insert_at_index(0, row_index.name.as_str().into(), row_index.data_type())?;
+            }
             
             self.cached_schema = Some(polars_schema);
-            self.cached_arrow_field = Some(arrow_field);
         }
         
         Ok(self.cached_schema.as_ref().unwrap())
     }
 
+    // String columns read as `Categorical` because their first batch has few distinct values,
+    // known once the schema is read
+    pub(crate) fn detected_dictionary_columns(&self) -> &[PlSmallStr] {
+        self.dictionaries.columns()
+    }
+
     /// Get basic info
     pub fn get_info(&self) -> &SasArrowReaderInfo {
         &self.info
     }
 
+    /// Format of the file, detected from its header
+    pub fn file_format(&self) -> SasFileFormat {
+        match &self.xport {
+            Some(xport) => xport.format(),
+            None => SasFileFormat::Sas7bdat,
+        }
+    }
+
     /// Get column information
     pub fn get_column_info(&self, column_index: u32) -> PolarsResult<(String, String)> {
+        if let Some(xport) = &self.xport {
+            let metadata = xport.metadata().columns.get(column_index as usize);
+            return match (metadata, xport.type_name(column_index as usize)) {
+                (Some(metadata), Some(type_name)) => Ok((metadata.name.clone(), type_name.to_string())),
+                _ => Err(PolarsError::OutOfBounds(format!("Column index {} out of range", column_index).into())),
+            };
+        }
+        
         let mut column_info = SasArrowColumnInfo {
             name: ptr::null(),
             type_name: ptr::null(),
@@ -257,10 +894,119 @@ impl SasReader {
         Ok((name, type_name))
     }
     
+    /// SAS metadata of the dataset and of its columns (labels, formats, lengths)
+    pub fn metadata(&self) -> PolarsResult<SasMetadata> {
+        if let Some(xport) = &self.xport {
+            return Ok(xport.metadata().clone());
+        }
+        
+        let to_string = |p: *const c_char| unsafe {
+            if p.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(p).to_string_lossy().to_string()
+            }
+        };
+        
+        let mut dataset = SasArrowDatasetMetadata {
+            dataset_name: ptr::null(),
+            encoding: ptr::null(),
+            sas_release: ptr::null(),
+            os_name: ptr::null(),
+            row_count: 0,
+        };
+        let result = unsafe { sas_arrow_reader_get_dataset_metadata(self.reader, &mut dataset) };
+        if result != SasArrowErrorCode::SasArrowOk {
+            return Err(Self::error_from_code(result));
+        }
+        
+        let mut columns = Vec::with_capacity(self.info.num_columns as usize);
+        for column_index in 0..self.info.num_columns {
+            let mut column = SasArrowColumnMetadata {
+                name: ptr::null(),
+                label: ptr::null(),
+                format: ptr::null(),
+                format_width: 0,
+                format_decimals: 0,
+                length: 0,
+            };
+            let result = unsafe { sas_arrow_reader_get_column_metadata(self.reader, column_index, &mut column) };
+            if result != SasArrowErrorCode::SasArrowOk {
+                return Err(Self::error_from_code(result));
+            }
+            columns.push(SasColumnMetadata {
+                name: to_string(column.name),
+                label: to_string(column.label),
+                format: to_string(column.format),
+                format_width: column.format_width,
+                format_decimals: column.format_decimals,
+                length: column.length,
+            });
+        }
+        
+        Ok(SasMetadata {
+            dataset_name: to_string(dataset.dataset_name),
+            label: String::new(),
+            encoding: to_string(dataset.encoding),
+            sas_release: to_string(dataset.sas_release),
+            os_name: to_string(dataset.os_name),
+            row_count: Some(dataset.row_count),
+            columns,
+        })
+    }
+    
     /// Read the next batch as a DataFrame
     pub fn read_next_batch(&mut self) -> PolarsResult<DataFrame> {
+        self.read_next_sas_batch().map(|batch| batch.df)
+    }
+    
+    /// Read the next batch with the location of its rows in the file
+    pub fn read_next_sas_batch(&mut self) -> PolarsResult<SasBatch> {
         self.get_schema()?;
         
+        let (mut df, first_row, page_range) = loop {
+            if self.next_row >= self.rows.end {
+                return Err(PolarsError::ComputeError("End of data reached".into()));
+            }
+            let (df, first_row, page_range) = match self.first_batch.take() {
+                Some(batch) => batch,
+                None => self.read_data_batch()?
+                    .ok_or_else(|| PolarsError::ComputeError("End of data reached".into()))?,
+            };
+            self.next_row = first_row + df.height() as u64;
+            if df.height() > 0 && self.next_row <= self.rows.start {
+                continue;
+            }
+            // Only the rows of `skip_rows..skip_rows + n_rows`
+            let start = first_row.max(self.rows.start);
+            let end = self.next_row.min(self.rows.end);
+            if start == first_row && end == self.next_row {
+                break (df, first_row, page_range);
+            }
+            break (df.slice((start - first_row) as i64, end.saturating_sub(start) as usize), start, page_range);
+        };
+        
+        self.dictionaries.apply(&mut df)?;
+        self.formatted_columns.apply(&mut df)?;
+        self.value_labels.apply(&mut df)?;
+        
+        if let Some(row_index) = &self.row_index {
+            df.insert_column(0, row_index.column(first_row, df.height())?)?;
+        }
+        
+        Ok(SasBatch { df, first_row, page_range })
+    }
+    
+    // Read the next batch of the file with its position, None at the end of the data
+    fn read_data_batch(&mut self) -> PolarsResult<Option<(DataFrame, u64, Range<u64>)>> {
+        match &mut self.xport {
+            Some(xport) => xport.next_batch(),
+            None => self.read_arrow_batch(),
+        }
+    }
+    
+    // Read the next batch of the C++ reader with its position
+    fn read_arrow_batch(&mut self) -> PolarsResult<Option<(DataFrame, u64, Range<u64>)>> {
         let mut c_array = CArrowArray::empty();
         
         let result = unsafe {
@@ -268,7 +1014,7 @@ impl SasReader {
         };
         
         if result == SasArrowErrorCode::SasArrowErrorEndOfData {
-            return Err(PolarsError::ComputeError("End of data reached".into()));
+            return Ok(None);
         }
         
         if result != SasArrowErrorCode::SasArrowOk {
@@ -279,7 +1025,100 @@ impl SasReader {
         let arrow_field = self.cached_arrow_field.as_ref().unwrap().clone();
         let df = self.arrow_to_dataframe_with_field(c_array, arrow_field)?;
         
-        Ok(df)
+        let mut position = SasArrowBatchPosition::default();
+        let result = unsafe {
+            sas_arrow_reader_get_batch_position(self.reader, &mut position)
+        };
+        
+        if result != SasArrowErrorCode::SasArrowOk {
+            return Err(Self::error_from_code(result));
+        }
+        
+        Ok(Some((df, position.first_row, position.first_page..position.last_page + 1)))
+    }
+    
+    /// Page layout of the file: the index of the page holding the first row (pages are
+    /// numbered from 0 after the header) and the number of pages
+    pub fn page_range(&self) -> PolarsResult<(u64, u64)> {
+        if self.xport.is_some() {
+            return Err(PolarsError::ComputeError("XPORT files have no pages".into()));
+        }
+        
+        let mut first_data_page = 0;
+        let mut page_count = 0;
+        let result = unsafe {
+            sas_arrow_reader_get_page_range(self.reader, &mut first_data_page, &mut page_count)
+        };
+        
+        if result != SasArrowErrorCode::SasArrowOk {
+            return Err(Self::error_from_code(result));
+        }
+        
+        Ok((first_data_page, page_count))
+    }
+    
+    /// Only read the rows of the pages `first_page..end_page`, see `page_range`.
+    /// Must be called before the first batch is read. The rows (`SasBatch::first_row`
+    /// and the row index) are then numbered from the first row of `first_page`.
+    pub fn set_page_range(&mut self, first_page: u64, end_page: u64) -> PolarsResult<()> {
+        if self.xport.is_some() {
+            return Err(PolarsError::ComputeError("XPORT files have no pages".into()));
+        }
+        if self.first_batch.is_some() {
+            return Err(PolarsError::ComputeError(
+                "The page range must be set before the schema is read when detecting dictionaries".into(),
+            ));
+        }
+        
+        let result = unsafe { sas_arrow_reader_set_page_range(self.reader, first_page, end_page) };
+        
+        if result != SasArrowErrorCode::SasArrowOk {
+            return Err(Self::error_from_code(result));
+        }
+        
+        Ok(())
+    }
+    
+    /// Values replaced by nulls in the last batch returned by `read_next_batch`.
+    /// Always empty unless the reader was opened with `SasErrorPolicy::Null`.
+    pub fn batch_diagnostics(&mut self) -> PolarsResult<Vec<SasDiagnostic>> {
+        // Every XPORT value can be converted
+        if self.xport.is_some() {
+            return Ok(Vec::new());
+        }
+        
+        let mut diagnostics: *const SasArrowDiagnostic = ptr::null();
+        let mut num_diagnostics: u32 = 0;
+        
+        let result = unsafe {
+            sas_arrow_reader_get_diagnostics(self.reader, &mut diagnostics, &mut num_diagnostics)
+        };
+        
+        if result != SasArrowErrorCode::SasArrowOk {
+            return Err(Self::error_from_code(result));
+        }
+        
+        if diagnostics.is_null() || num_diagnostics == 0 {
+            return Ok(Vec::new());
+        }
+        
+        let to_string = |p: *const c_char| unsafe {
+            if p.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(p).to_string_lossy().to_string()
+            }
+        };
+        
+        let diagnostics = unsafe { std::slice::from_raw_parts(diagnostics, num_diagnostics as usize) };
+        Ok(diagnostics.iter()
+            .map(|d| SasDiagnostic {
+                row: d.row,
+                column_index: d.column_index,
+                column_name: to_string(d.column_name),
+                message: to_string(d.message),
+            })
+            .collect())
     }
     
     /// Convert Arrow C Data Interface to Polars DataFrame using cached field
@@ -331,13 +1170,13 @@ impl SasReader {
             _ => return Err(PolarsError::ComputeError("Expected struct data type from SAS data".into())),
         };
         
-        let mut schema_map = std::collections::BTreeMap::new();
+        // The columns keep their order in the file, as in the batches
+        let mut polars_schema = Schema::with_capacity(struct_fields.len());
         for struct_field in struct_fields {
             let polars_dtype = self.arrow_dtype_to_polars(&struct_field.dtype)?;
-            schema_map.insert(struct_field.name.clone(), polars_dtype);
+            polars_schema.insert(struct_field.name.clone(), polars_dtype);
         }
         
-        let polars_schema = Schema::from_iter(schema_map);
         Ok((polars_schema, field))
     }
     
@@ -346,8 +1185,9 @@ impl SasReader {
         use polars_arrow::datatypes::ArrowDataType;
         
         let polars_type = match arrow_type {
-            // SAS string columns -> UTF8
-            ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 => DataType::String,
+            // SAS string columns -> UTF8 (views are imported as-is, they are the Polars layout)
+            ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 | ArrowDataType::Utf8View => DataType::String,
+            ArrowDataType::BinaryView => DataType::Binary,
             
             // SAS integer columns -> Int64
             ArrowDataType::Int64 => DataType::Int64,
@@ -372,6 +1212,16 @@ impl SasReader {
             // SAS time columns -> Time64 with microsecond precision
             ArrowDataType::Time64(_) => DataType::Time,
             
+            // SAS dictionary-encoded string columns -> Categorical
+            ArrowDataType::Dictionary(_, value_type, _)
+                if matches!(value_type.as_ref(), ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 | ArrowDataType::Utf8View) =>
+            {
+                DataType::Categorical(None, CategoricalOrdering::Physical)
+            },
+            
+            // SAS fixed-decimal columns (w.d, COMMAw.d, DOLLARw.d) -> Decimal
+            ArrowDataType::Decimal(precision, scale) => DataType::Decimal(Some(*precision), Some(*scale)),
+            
             // Fallback for any unexpected types
             _ => {
                 return Err(PolarsError::ComputeError(
@@ -385,6 +1235,12 @@ impl SasReader {
     
     /// Reset the reader (may not be implemented in your C++ code yet)
     pub fn reset(&mut self) -> PolarsResult<()> {
+        self.next_row = 0;
+        self.first_batch = None;
+        if let Some(xport) = &mut self.xport {
+            return xport.reset();
+        }
+        
         let result = unsafe { sas_arrow_reader_reset(self.reader) };
         
         if result != SasArrowErrorCode::SasArrowOk {
@@ -435,6 +1291,10 @@ impl SasReader {
     }
 }
 
+// SAFETY: the C++ reader owns its data source and its sink, nothing is shared with other
+// readers, so it can be used from any thread (one at a time, `&mut self` methods)
+unsafe impl Send for SasReader {}
+
 impl Drop for SasReader {
     fn drop(&mut self) {
         if !self.reader.is_null() {
@@ -461,27 +1321,90 @@ impl SasBatchIterator {
         })
     }
 
+    /// Create a new streaming iterator over a `.sas7bdat` or a transport (XPORT) file
+    pub fn open(file_path: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::open(file_path, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
+    /// Create a new streaming iterator with explicit read options
+    pub fn with_options(file_path: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::with_options(file_path, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
+    /// Create a new streaming iterator over a SAS file held in memory
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::from_bytes(bytes, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
+    /// Create a new streaming iterator over a member of a ZIP or TAR archive
+    pub fn open_archive_member(archive_path: &str, member_name: &str, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::open_archive_member(archive_path, member_name, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
+    /// Create a new streaming iterator over a remote file read with HTTP `Range` requests
+    #[cfg(feature = "http")]
+    pub fn from_url(url: &str, http_options: SasHttpOptions, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::from_url(url, http_options, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
+    /// Create a new streaming iterator over any seekable byte stream
+    pub fn from_reader<R: Read + Seek + Send + 'static>(source: R, options: SasReadOptions) -> PolarsResult<Self> {
+        let reader = SasReader::from_reader(source, options)?;
+        Ok(SasBatchIterator {
+            reader,
+            finished: false,
+        })
+    }
+
     /// Get the schema without reading any data
     pub fn schema(&mut self) -> PolarsResult<&Schema> {
         self.reader.get_schema()
     }
 
+    /// SAS metadata of the dataset and of its columns
+    pub fn metadata(&self) -> PolarsResult<SasMetadata> {
+        self.reader.metadata()
+    }
+
+    /// Decode the batches on a background thread, up to `queue_size` batches ahead
+    /// of the consumer
+    pub fn prefetch(self, queue_size: usize) -> PolarsResult<SasPrefetchIterator> {
+        SasPrefetchIterator::new(self, queue_size)
+    }
+
     /// Get reader info
     pub fn info(&self) -> &SasArrowReaderInfo {
         &self.reader.info
     }
-}
 
-impl Iterator for SasBatchIterator {
-    type Item = PolarsResult<DataFrame>;
-    
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Read the next batch with the location of its rows in the file
+    pub fn next_sas_batch(&mut self) -> Option<PolarsResult<SasBatch>> {
         if self.finished {
             return None;
         }
         
-        match self.reader.read_next_batch() {
-            Ok(df) => Some(Ok(df)),
+        match self.reader.read_next_sas_batch() {
+            Ok(batch) => Some(Ok(batch)),
             Err(e) => {
                 if e.to_string().contains("End of data") {
                     self.finished = true;
@@ -493,6 +1416,19 @@ impl Iterator for SasBatchIterator {
             }
         }
     }
+
+    /// Iterate over the batches with the location of their rows in the file
+    pub fn sas_batches(mut self) -> impl Iterator<Item = PolarsResult<SasBatch>> {
+        std::iter::from_fn(move || self.next_sas_batch())
+    }
+}
+
+impl Iterator for SasBatchIterator {
+    type Item = PolarsResult<DataFrame>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sas_batch().map(|batch| batch.map(|batch| batch.df))
+    }
 }
 
 // Convenience functions
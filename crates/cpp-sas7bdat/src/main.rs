@@ -5,7 +5,17 @@ use polars::frame::DataFrame;
 
 fn main() {
     // let path = "/home/jrothbaum/Coding/polars_readstat/crates/readstat-tests/tests/data/pyreadstat/basic/sample.sas7bdat";
-    let path = "/home/jrothbaum/Downloads/sas_pil/psam_p17.sas7bdat";
+    // A SAS file, a path inside a ZIP/TAR archive (vendor.zip/member.sas7bdat) or an archive to list
+    let path = std::env::args().nth(1)
+        .unwrap_or_else(|| "/home/jrothbaum/Downloads/sas_pil/psam_p17.sas7bdat".to_string());
+    let path = path.as_str();
+    
+    if let Ok(members) = SasReader::list_archive_members(path) {
+        for member in members {
+            println!("{}  {} bytes ({} compressed)", member.name, member.size, member.compressed_size);
+        }
+        return;
+    }
     
     let start_schema = Instant::now();
     let schema = match SasReader::read_sas_schema(path) {
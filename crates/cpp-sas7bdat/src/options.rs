@@ -0,0 +1,758 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use polars::prelude::*;
+use polars_arrow::datatypes::ArrowSchema;
+
+use crate::{SasCatalog, SasColumnMetadata, SasMetadata, SasValueLabels};
+
+/// Temporal type a SAS format name is mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasTemporalType {
+    /// Keep the raw numeric value
+    Number,
+    Date,
+    Datetime,
+    Time,
+}
+
+/// Arrow layout used for the string columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasStringLayout {
+    /// Offsets + contiguous data, converted by Polars on import
+    #[default]
+    Utf8,
+    /// Views with short values inlined, imported by Polars without copy
+    Utf8View,
+    /// As `Utf8View` without the UTF-8 guarantee, read as `Binary`
+    BinaryView,
+}
+
+/// What to do with a value that cannot be converted to its Arrow column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasErrorPolicy {
+    /// Fail the batch with an error naming the row and the column
+    #[default]
+    Abort,
+    /// Store a null and report it in `SasReader::batch_diagnostics`
+    Null,
+}
+
+/// How the value labels of a format are applied to its columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasValueLabelMode {
+    /// Read the column as a `Categorical` of the labels
+    #[default]
+    Replace,
+    /// Keep the column and add the `Categorical` of the labels after it, as `<column>_label`
+    Companion,
+}
+
+/// Integer type of the row index column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasRowIndexType {
+    UInt32,
+    #[default]
+    UInt64,
+}
+
+/// Column numbering the rows of a file, prepended to every batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SasRowIndex {
+    pub name: String,
+    /// Index of the first row of the file
+    pub offset: u64,
+    pub dtype: SasRowIndexType,
+}
+
+impl SasRowIndex {
+    // The index column of `height` rows starting at the row `first_row` of the file
+    pub(crate) fn column(&self, first_row: u64, height: usize) -> PolarsResult<Column> {
+        let start = self.offset.checked_add(first_row);
+        let end = start.and_then(|start| start.checked_add(height as u64));
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(PolarsError::ComputeError(
+                format!("The row index '{}' overflows UInt64", self.name).into(),
+            ));
+        };
+        let column = match self.dtype {
+            SasRowIndexType::UInt64 => {
+                UInt64Chunked::from_iter_values(self.name.as_str().into(), start..end).into_column()
+            }
+            SasRowIndexType::UInt32 => {
+                if end > u32::MAX as u64 + 1 {
+                    return Err(PolarsError::ComputeError(
+                        format!("The row index '{}' overflows UInt32, use UInt64", self.name).into(),
+                    ));
+                }
+                UInt32Chunked::from_iter_values(self.name.as_str().into(), start as u32..end as u32).into_column()
+            }
+        };
+        Ok(column)
+    }
+
+    pub(crate) fn data_type(&self) -> DataType {
+        match self.dtype {
+            SasRowIndexType::UInt32 => DataType::UInt32,
+            SasRowIndexType::UInt64 => DataType::UInt64,
+        }
+    }
+}
+
+/// Options used when opening a SAS file
+#[derive(Debug, Clone)]
+pub struct SasReadOptions {
+    /// Rows per batch, None uses the default (65536)
+    pub chunk_size: Option<u32>,
+    /// Classify numeric columns as date/datetime/time using the built-in format tables
+    pub detect_temporal: bool,
+    /// Extra format name -> temporal type mappings, they take precedence over the built-in tables
+    pub temporal_formats: Vec<(String, SasTemporalType)>,
+    /// Read numeric columns formatted `w.d`, `COMMAw.d` or `DOLLARw.d` as `Decimal(w, d)`
+    pub fixed_decimal: bool,
+    /// String columns read as `Categorical`
+    pub dictionary_columns: Vec<String>,
+    /// Also read string columns with at most this many distinct values in the first batch as `Categorical`
+    pub dictionary_max_cardinality: Option<u32>,
+    /// Arrow layout of the string columns
+    pub string_layout: SasStringLayout,
+    /// Handling of values that cannot be converted (e.g. a decimal overflow)
+    pub on_error: SasErrorPolicy,
+    /// Map the file in memory and decode the pages in place instead of reading them
+    pub mmap: bool,
+    /// Advise the kernel that the mapping is read sequentially (`madvise(MADV_SEQUENTIAL)`)
+    pub sequential_access: bool,
+    /// Rows skipped at the start of the file
+    pub skip_rows: u64,
+    /// Rows read after the skipped ones, None reads them all
+    pub n_rows: Option<u64>,
+    /// Column numbering the rows, prepended to every batch
+    pub row_index: Option<SasRowIndex>,
+    /// Format name -> values and their labels, applied to the columns with this format
+    pub value_labels: HashMap<String, SasValueLabels>,
+    /// Replace the labelled columns or add a label column
+    pub value_label_mode: SasValueLabelMode,
+    /// Numeric columns read as the strings of their format, or of the format given
+    pub formatted_columns: Vec<(String, Option<String>)>,
+}
+
+impl Default for SasReadOptions {
+    fn default() -> Self {
+        SasReadOptions {
+            chunk_size: None,
+            detect_temporal: true,
+            temporal_formats: Vec::new(),
+            fixed_decimal: false,
+            dictionary_columns: Vec::new(),
+            dictionary_max_cardinality: None,
+            string_layout: SasStringLayout::default(),
+            on_error: SasErrorPolicy::default(),
+            mmap: false,
+            sequential_access: false,
+            skip_rows: 0,
+            n_rows: None,
+            row_index: None,
+            value_labels: HashMap::new(),
+            value_label_mode: SasValueLabelMode::default(),
+            formatted_columns: Vec::new(),
+        }
+    }
+}
+
+impl SasReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of rows per batch
+    pub fn with_chunk_size(mut self, chunk_size: u32) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Enable or disable the built-in date/datetime/time format tables.
+    /// Formats registered with `with_temporal_format` are still applied when disabled.
+    pub fn with_temporal_detection(mut self, detect_temporal: bool) -> Self {
+        self.detect_temporal = detect_temporal;
+        self
+    }
+
+    /// Map a SAS format name (without width/decimals, e.g. "YYMMDD") to a temporal type.
+    /// Matching is case-insensitive.
+    pub fn with_temporal_format(mut self, format_name: &str, temporal_type: SasTemporalType) -> Self {
+        self.temporal_formats.push((format_name.to_string(), temporal_type));
+        self
+    }
+
+    /// Read numeric columns with a fixed-decimal format (`w.d`, `Fw.d`, `COMMAw.d`,
+    /// `DOLLARw.d` and their `X` variants) as `Decimal(precision, scale)` instead of `Float64`.
+    ///
+    /// The scale is the format decimals and the precision the format width (at least
+    /// scale + 1, at most 38). Values are rounded to the scale to nearest, ties to even,
+    /// starting from the stored binary double (1.005 is stored as 1.00499... and reads as 1.00).
+    /// A value that does not fit in the precision is handled according to the error policy.
+    pub fn with_fixed_decimal(mut self, fixed_decimal: bool) -> Self {
+        self.fixed_decimal = fixed_decimal;
+        self
+    }
+
+    /// Read the string column `column_name` as `Categorical`.
+    ///
+    /// The C++ reader keeps one dictionary per column for the whole file and the
+    /// categories are registered in the global string cache, held by the reader and its
+    /// iterators, so their batches can be concatenated without re-encoding. Hold a
+    /// `StringCacheHolder` to combine the frames of readers that are not open at the same
+    /// time. The columns are not read as `Enum`: their categories are only known once the
+    /// whole file is read.
+    pub fn with_dictionary_column(mut self, column_name: &str) -> Self {
+        self.dictionary_columns.push(column_name.to_string());
+        self
+    }
+
+    /// Read every string column with at most `max_cardinality` distinct values in the
+    /// first batch as `Categorical`, as `with_dictionary_column` does. The first batch is
+    /// decoded when the schema is read and kept for the first call to `read_next_batch`,
+    /// so the file is read once.
+    pub fn with_dictionary_detection(mut self, max_cardinality: u32) -> Self {
+        self.dictionary_max_cardinality = Some(max_cardinality);
+        self
+    }
+
+    /// Set the Arrow layout of the string columns
+    pub fn with_string_layout(mut self, string_layout: SasStringLayout) -> Self {
+        self.string_layout = string_layout;
+        self
+    }
+
+    /// Set the handling of values that cannot be converted
+    pub fn with_error_policy(mut self, on_error: SasErrorPolicy) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Memory-map the file instead of reading it page by page. The rows are decoded
+    /// directly from the mapping, without copying the pages. The file must not be
+    /// modified while it is read. Ignored for compressed files.
+    pub fn with_mmap(mut self, mmap: bool) -> Self {
+        self.mmap = mmap;
+        self
+    }
+
+    /// With `with_mmap`, call `madvise(MADV_SEQUENTIAL)` on the mapping so the kernel
+    /// reads ahead aggressively and drops the pages already read. No effect on non-Unix.
+    pub fn with_sequential_access(mut self, sequential_access: bool) -> Self {
+        self.sequential_access = sequential_access;
+        self
+    }
+
+    /// Skip the first `skip_rows` rows of the file. The skipped rows are still decoded.
+    /// Every file of a `SasDatasetReader` skips its own first rows.
+    pub fn with_skip_rows(mut self, skip_rows: u64) -> Self {
+        self.skip_rows = skip_rows;
+        self
+    }
+
+    /// Read at most `n_rows` rows, after the skipped ones: the reader stops once they are
+    /// read. Every file of a `SasDatasetReader` reads at most `n_rows` rows.
+    pub fn with_n_rows(mut self, n_rows: u64) -> Self {
+        self.n_rows = Some(n_rows);
+        self
+    }
+
+    /// Prepend a column `name` numbering the rows of the file from `offset`.
+    ///
+    /// The index is the physical row number in the file, skipped rows included,
+    /// `SasParallelBatchIterator` numbers the rows as a sequential read does. Every file of
+    /// a `SasDatasetReader` is numbered from `offset`. A `UInt32` index fails past `u32::MAX`.
+    pub fn with_row_index(mut self, name: &str, offset: u64, dtype: SasRowIndexType) -> Self {
+        self.row_index = Some(SasRowIndex { name: name.to_string(), offset, dtype });
+        self
+    }
+
+    /// Label the values of the columns whose format is one of the formats of `catalog`.
+    ///
+    /// Values are labelled by the first range holding them, then by `OTHER`; missing values
+    /// take the label of `.`. The numeric columns whose format labels a special missing value
+    /// `.A`-`.Z` or `._` keep its tag, and their missing values are labelled by it: they are
+    /// read as `Float64` NaNs without labels, as nulls beside their labels in companion mode.
+    /// Values without a label keep their text, missing ones are null.
+    pub fn with_catalog(mut self, catalog: &SasCatalog) -> Self {
+        for (format, labels) in &catalog.formats {
+            self.value_labels.insert(format.to_ascii_uppercase(), labels.clone());
+        }
+        self
+    }
+
+    /// Label the values of the columns with the format `format` (e.g. `SEXF` or `$REGION`),
+    /// see `with_catalog`
+    pub fn with_value_labels(mut self, format: &str, labels: SasValueLabels) -> Self {
+        self.value_labels.insert(format.to_ascii_uppercase(), labels);
+        self
+    }
+
+    /// Set whether the labels replace the values or are added as `<column>_label`
+    pub fn with_value_label_mode(mut self, value_label_mode: SasValueLabelMode) -> Self {
+        self.value_label_mode = value_label_mode;
+        self
+    }
+
+    /// Read the numeric column `column_name` as the strings its SAS format renders, without
+    /// their leading blanks, see `sas_put`. Columns without format are rendered with `BEST12.`.
+    pub fn with_formatted_column(mut self, column_name: &str) -> Self {
+        self.formatted_columns.push((column_name.to_string(), None));
+        self
+    }
+
+    /// Read the numeric column `column_name` as the strings `format` renders, e.g. `COMMA12.2`
+    pub fn with_put_format(mut self, column_name: &str, format: &str) -> Self {
+        self.formatted_columns.push((column_name.to_string(), Some(format.to_string())));
+        self
+    }
+
+    // Rows read, from `skip_rows` to `skip_rows + n_rows`
+    pub(crate) fn rows(&self) -> Range<u64> {
+        self.skip_rows..self.n_rows.map_or(u64::MAX, |n_rows| self.skip_rows.saturating_add(n_rows))
+    }
+
+    /// Whether any column may be read as `Categorical`
+    pub(crate) fn uses_dictionaries(&self) -> bool {
+        !self.dictionary_columns.is_empty()
+            || self.dictionary_max_cardinality.unwrap_or(0) > 0
+            || !self.value_labels.is_empty()
+    }
+}
+
+// The format of a column as written, e.g. `COMMA12.2`, None without format
+pub(crate) fn format_spec(column: &SasColumnMetadata) -> Option<String> {
+    if column.format.is_empty() {
+        return None;
+    }
+    let format = match (column.format_width, column.format_decimals) {
+        (0, 0) => format!("{}.", column.format),
+        (width, 0) => format!("{}{}.", column.format, width),
+        (width, decimals) => format!("{}{}.{}", column.format, width, decimals),
+    };
+    Some(format)
+}
+
+/// Version of the transport files written by `write_xpt`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasXportVersion {
+    /// Names of 8 characters, labels of 40 and strings of 200 bytes
+    #[default]
+    V5,
+    /// Names of 32 characters, labels of 256 and strings of 32767 bytes
+    V8,
+}
+
+/// Options used when writing a transport file
+#[derive(Debug, Clone)]
+pub struct SasXportWriteOptions {
+    pub version: SasXportVersion,
+    /// Name of the dataset (member), `DATA` by default
+    pub dataset_name: String,
+    pub dataset_label: String,
+    /// Column name -> label
+    pub labels: Vec<(String, String)>,
+    /// Column name -> format (e.g. `COMMA12.2`), temporal columns default to
+    /// `DATE9.`, `DATETIME20.` and `TIME8.`
+    pub formats: Vec<(String, String)>,
+    /// Truncate the names, labels and strings over the limits of the version instead of failing
+    pub truncate: bool,
+}
+
+impl Default for SasXportWriteOptions {
+    fn default() -> Self {
+        SasXportWriteOptions {
+            version: SasXportVersion::default(),
+            dataset_name: "DATA".to_string(),
+            dataset_label: String::new(),
+            labels: Vec::new(),
+            formats: Vec::new(),
+            truncate: false,
+        }
+    }
+}
+
+impl SasXportWriteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the transport file version
+    pub fn with_version(mut self, version: SasXportVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the name of the dataset
+    pub fn with_dataset_name(mut self, dataset_name: &str) -> Self {
+        self.dataset_name = dataset_name.to_string();
+        self
+    }
+
+    /// Set the label of the dataset (40 characters at most)
+    pub fn with_dataset_label(mut self, dataset_label: &str) -> Self {
+        self.dataset_label = dataset_label.to_string();
+        self
+    }
+
+    /// Set the label of the column `column_name`
+    pub fn with_label(mut self, column_name: &str, label: &str) -> Self {
+        self.labels.push((column_name.to_string(), label.to_string()));
+        self
+    }
+
+    /// Set the format of the column `column_name`, e.g. `COMMA12.2`
+    pub fn with_format(mut self, column_name: &str, format: &str) -> Self {
+        self.formats.push((column_name.to_string(), format.to_string()));
+        self
+    }
+
+    /// Take the dataset name and label and the column labels and formats of a file read
+    /// with `SasReader::metadata`, to write it back with its SAS metadata.
+    pub fn with_metadata(mut self, metadata: &SasMetadata) -> Self {
+        if !metadata.dataset_name.is_empty() {
+            self.dataset_name = metadata.dataset_name.clone();
+        }
+        self.dataset_label = metadata.label.clone();
+        for column in &metadata.columns {
+            if !column.label.is_empty() {
+                self.labels.push((column.name.clone(), column.label.clone()));
+            }
+            if let Some(format) = format_spec(column) {
+                self.formats.push((column.name.clone(), format));
+            }
+        }
+        self
+    }
+
+    /// Take the column labels and formats from the `label` and `format` keys of the field
+    /// metadata of an Arrow schema (e.g. the schema of a Parquet or IPC file)
+    pub fn with_field_metadata(mut self, schema: &ArrowSchema) -> Self {
+        for field in schema.iter_values() {
+            let Some(metadata) = &field.metadata else {
+                continue;
+            };
+            if let Some(label) = metadata.get("label") {
+                self.labels.push((field.name.to_string(), label.to_string()));
+            }
+            if let Some(format) = metadata.get("format") {
+                self.formats.push((field.name.to_string(), format.to_string()));
+            }
+        }
+        self
+    }
+
+    /// Truncate the values over the limits of the version instead of failing
+    pub fn with_truncation(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+}
+
+/// Compression of the rows of a `.sas7bdat` file written by `SasWriter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasCompression {
+    #[default]
+    None,
+    /// Run-length encoding (`SASYZCRL`, `COMPRESS=CHAR`), for rows padded with blanks
+    Rle,
+    /// Ross data compression (`SASYZCR2`, `COMPRESS=BINARY`), also for repeated patterns
+    Rdc,
+}
+
+/// Options used when writing a `.sas7bdat` file with `SasWriter`
+#[derive(Debug, Clone)]
+pub struct SasWriteOptions {
+    /// Name of the dataset, `DATA` by default
+    pub dataset_name: String,
+    /// Column name -> label
+    pub labels: Vec<(String, String)>,
+    /// Column name -> format (e.g. `COMMA12.2`), temporal columns default to
+    /// `DATE9.`, `DATETIME20.` and `TIME8.`
+    pub formats: Vec<(String, String)>,
+    /// Column name -> length in bytes of a string column, the longest value of the first
+    /// batch by default
+    pub string_lengths: Vec<(String, u32)>,
+    /// Truncate the names, labels and strings over their limits instead of failing
+    pub truncate: bool,
+    /// Size of the pages in bytes, enlarged to hold at least one row
+    pub page_length: u32,
+    /// Compression of the rows, none by default
+    pub compression: SasCompression,
+}
+
+impl Default for SasWriteOptions {
+    fn default() -> Self {
+        SasWriteOptions {
+            dataset_name: "DATA".to_string(),
+            labels: Vec::new(),
+            formats: Vec::new(),
+            string_lengths: Vec::new(),
+            truncate: false,
+            page_length: 65536,
+            compression: SasCompression::None,
+        }
+    }
+}
+
+impl SasWriteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the dataset
+    pub fn with_dataset_name(mut self, dataset_name: &str) -> Self {
+        self.dataset_name = dataset_name.to_string();
+        self
+    }
+
+    /// Set the label of the column `column_name`
+    pub fn with_label(mut self, column_name: &str, label: &str) -> Self {
+        self.labels.push((column_name.to_string(), label.to_string()));
+        self
+    }
+
+    /// Set the format of the column `column_name`, e.g. `COMMA12.2`
+    pub fn with_format(mut self, column_name: &str, format: &str) -> Self {
+        self.formats.push((column_name.to_string(), format.to_string()));
+        self
+    }
+
+    /// Reserve `length` bytes for the values of the string column `column_name`, for the
+    /// longer values of the batches after the first one
+    pub fn with_string_length(mut self, column_name: &str, length: u32) -> Self {
+        self.string_lengths.push((column_name.to_string(), length));
+        self
+    }
+
+    /// Take the dataset name and the column labels, formats and string lengths of a file
+    /// read with `SasReader::metadata`, to write it back with its SAS metadata.
+    pub fn with_metadata(mut self, metadata: &SasMetadata) -> Self {
+        if !metadata.dataset_name.is_empty() {
+            self.dataset_name = metadata.dataset_name.clone();
+        }
+        for column in &metadata.columns {
+            if !column.label.is_empty() {
+                self.labels.push((column.name.clone(), column.label.clone()));
+            }
+            if let Some(format) = format_spec(column) {
+                self.formats.push((column.name.clone(), format));
+            }
+            self.string_lengths.push((column.name.clone(), column.length));
+        }
+        self
+    }
+
+    /// Truncate the values over their limits (32 bytes for names, 256 for labels, the
+    /// column length for strings) instead of failing
+    pub fn with_truncation(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Set the size of the pages
+    pub fn with_page_length(mut self, page_length: u32) -> Self {
+        self.page_length = page_length;
+        self
+    }
+
+    /// Compress the rows, stored one by one in the pages; a row which does not shrink is
+    /// stored as it is
+    pub fn with_compression(mut self, compression: SasCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+/// Options of the HTTP data source used by `SasReader::from_url`
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct SasHttpOptions {
+    /// Size of the blocks fetched with `Range` requests, in bytes
+    pub block_size: u64,
+    /// Number of blocks kept in memory, the least recently used ones are dropped first
+    pub cache_blocks: usize,
+    /// Extra blocks fetched in the same request once the file is read sequentially
+    pub read_ahead: usize,
+    /// Extra request headers (e.g. `Authorization`)
+    pub headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "http")]
+impl Default for SasHttpOptions {
+    fn default() -> Self {
+        SasHttpOptions {
+            block_size: 64 * 1024,
+            cache_blocks: 64,
+            read_ahead: 15,
+            headers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl SasHttpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the size of the fetched blocks
+    pub fn with_block_size(mut self, block_size: u64) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Set the number of cached blocks
+    pub fn with_cache_blocks(mut self, cache_blocks: usize) -> Self {
+        self.cache_blocks = cache_blocks;
+        self
+    }
+
+    /// Set the number of blocks read ahead during sequential reads, 0 disables it
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Add a header sent with every request
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Options used when converting a SAS file with `sas_to_parquet`
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone)]
+pub struct SasParquetOptions {
+    /// Options of the reader of the SAS file
+    pub read_options: SasReadOptions,
+    /// Rows per row group, None (or 0) writes every batch read as a row group
+    pub row_group_size: Option<usize>,
+    /// Compression of the column chunks, zstd by default
+    pub compression: ParquetCompression,
+    /// Write the min/max/null count statistics of the column chunks
+    pub statistics: bool,
+}
+
+#[cfg(feature = "parquet")]
+impl Default for SasParquetOptions {
+    fn default() -> Self {
+        SasParquetOptions {
+            read_options: SasReadOptions::default(),
+            row_group_size: None,
+            compression: ParquetCompression::Zstd(None),
+            statistics: true,
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl SasParquetOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the options of the reader, its chunk size is the size of the row groups by default
+    pub fn with_read_options(mut self, read_options: SasReadOptions) -> Self {
+        self.read_options = read_options;
+        self
+    }
+
+    /// Set the number of rows per row group, whatever the size of the batches read
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = Some(row_group_size.max(1));
+        self
+    }
+
+    /// Set the compression of the column chunks
+    pub fn with_compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Write the statistics of the column chunks or not
+    pub fn with_statistics(mut self, statistics: bool) -> Self {
+        self.statistics = statistics;
+        self
+    }
+}
+
+/// Arrow IPC format written by `sas_to_ipc`
+#[cfg(feature = "ipc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasIpcFormat {
+    /// File format (Feather v2), with a footer indexing the record batches
+    #[default]
+    File,
+    /// Stream format, record batches only, for pipes
+    Stream,
+}
+
+/// Compression of the buffers of the record batches written by `sas_to_ipc`
+#[cfg(feature = "ipc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SasIpcCompression {
+    None,
+    Lz4,
+    #[default]
+    Zstd,
+}
+
+/// Options used when converting a SAS file with `sas_to_ipc`
+#[cfg(feature = "ipc")]
+#[derive(Debug, Clone)]
+pub struct SasIpcOptions {
+    /// Options of the reader of the SAS file, every batch read is a record batch
+    pub read_options: SasReadOptions,
+    pub format: SasIpcFormat,
+    /// Compression of the buffers, zstd by default
+    pub compression: SasIpcCompression,
+    /// Arrow types of the columns (e.g. `LargeUtf8` or `Utf8View` strings)
+    pub compat_level: CompatLevel,
+}
+
+#[cfg(feature = "ipc")]
+impl Default for SasIpcOptions {
+    fn default() -> Self {
+        SasIpcOptions {
+            read_options: SasReadOptions::default(),
+            format: SasIpcFormat::default(),
+            compression: SasIpcCompression::default(),
+            compat_level: CompatLevel::newest(),
+        }
+    }
+}
+
+#[cfg(feature = "ipc")]
+impl SasIpcOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the options of the reader, its chunk size is the size of the record batches
+    pub fn with_read_options(mut self, read_options: SasReadOptions) -> Self {
+        self.read_options = read_options;
+        self
+    }
+
+    /// Write the file or the stream format
+    pub fn with_format(mut self, format: SasIpcFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the compression of the buffers
+    pub fn with_compression(mut self, compression: SasIpcCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the Arrow types of the columns, `CompatLevel::oldest()` for readers without view types
+    pub fn with_compat_level(mut self, compat_level: CompatLevel) -> Self {
+        self.compat_level = compat_level;
+        self
+    }
+}
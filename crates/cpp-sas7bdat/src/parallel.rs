@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, JoinHandle};
+
+use polars::prelude::*;
+
+use crate::utilities::get_max_threads;
+use crate::{archive, compression, string_cache_for};
+use crate::{SasBatch, SasBatchIterator, SasFileFormat, SasReadOptions, SasReader, SasRowIndex};
+
+// Page ranges per thread: smaller ranges balance the work better between the threads
+const RANGES_PER_THREAD: u64 = 4;
+// Decoded batches of a range waiting to be yielded
+const QUEUED_BATCHES: usize = 2;
+
+/// Streaming iterator decoding a file with several threads.
+///
+/// The data pages are split in ranges decoded by readers running in parallel, each with
+/// its own data source, `POLARS_MAX_THREADS` at a time. Each range is decoded on its own
+/// thread into a queue of a few batches, so at most a few batches per thread are held in
+/// memory. The batches are yielded in file order as soon as they are decoded; each page
+/// range ends with a batch shorter than the chunk size. The rows are numbered
+/// (`SasBatch::first_row` and the row index), skipped and limited as in a sequential read.
+/// Dropping the iterator stops the threads after the batch they decode.
+///
+/// Inputs whose pages cannot be read without the pages before them are read sequentially,
+/// by a single reader: whole-file compressed files (`.gz`, ...), archive members, remote
+/// files and transport (XPORT) files.
+pub struct SasParallelBatchIterator {
+    file_path: String,
+    // Options of the range readers, without the row index, the skipped rows and the limit
+    options: SasReadOptions,
+    row_index: Option<SasRowIndex>,
+    // Rows yielded, from `skip_rows` to `skip_rows + n_rows`
+    rows_read: Range<u64>,
+    schema: Schema,
+    // Reader of the inputs read sequentially
+    sequential: Option<SasBatchIterator>,
+    threads: usize,
+    // Page ranges not decoded yet, in file order
+    ranges: VecDeque<(u64, u64)>,
+    // Ranges being decoded, in file order: the batches of the first one are yielded
+    workers: VecDeque<RangeWorker>,
+    // Rows decoded so far, in file order
+    rows: u64,
+    _string_cache: Option<polars_core::StringCacheHolder>,
+}
+
+// A page range decoded on its own thread, rows numbered from the range
+struct RangeWorker {
+    receiver: Receiver<PolarsResult<SasBatch>>,
+    worker: JoinHandle<()>,
+}
+
+impl SasParallelBatchIterator {
+    /// Create a parallel iterator over a file read with `SasReader::with_options`
+    pub fn new(file_path: &str, mut options: SasReadOptions) -> PolarsResult<Self> {
+        let threads = get_max_threads().max(1);
+
+        let mut reader = SasReader::with_options(file_path, options.clone())?;
+        let schema = reader.get_schema()?.clone();
+        // The range readers read the dictionary columns detected on the first batch of the file
+        let detected = reader.detected_dictionary_columns().iter().map(|name| name.to_string());
+        options.dictionary_columns.extend(detected);
+        options.dictionary_max_cardinality = None;
+
+        let (sequential, ranges) = if seeks_pages(file_path, &reader) {
+            (None, page_ranges(&reader, threads)?)
+        } else {
+            (Some(SasBatchIterator { reader, finished: false }), VecDeque::new())
+        };
+
+        // The rows are numbered, skipped and limited in file order, once the rows before each
+        // batch are known
+        let row_index = options.row_index.take();
+        let rows_read = options.rows();
+        options.skip_rows = 0;
+        options.n_rows = None;
+        let string_cache = string_cache_for(&options);
+        Ok(SasParallelBatchIterator {
+            file_path: file_path.to_string(),
+            options,
+            row_index,
+            rows_read,
+            schema,
+            sequential,
+            threads,
+            ranges,
+            workers: VecDeque::new(),
+            rows: 0,
+            _string_cache: string_cache,
+        })
+    }
+
+    /// Get the schema without reading any data
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    // Decode the rows of the pages `first_page..end_page` with a new reader on a new thread
+    fn start_range(&self, (first_page, end_page): (u64, u64)) -> PolarsResult<RangeWorker> {
+        let file_path = self.file_path.clone();
+        let options = self.options.clone();
+        let (sender, receiver) = sync_channel(QUEUED_BATCHES);
+        let worker = thread::Builder::new()
+            .name("sas-parallel".to_string())
+            .spawn(move || {
+                let reader = SasReader::with_options(&file_path, options)
+                    .and_then(|mut reader| reader.set_page_range(first_page, end_page).map(|_| reader));
+                let reader = match reader {
+                    Ok(reader) => reader,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
+                };
+                for batch in (SasBatchIterator { reader, finished: false }).sas_batches() {
+                    if matches!(&batch, Ok(batch) if batch.df.height() == 0) {
+                        continue;
+                    }
+                    let failed = batch.is_err();
+                    // Stops when the iterator is dropped or after an error
+                    if sender.send(batch).is_err() || failed {
+                        return;
+                    }
+                }
+            })?;
+        Ok(RangeWorker { receiver, worker })
+    }
+
+    // Keep `threads` ranges decoding
+    fn start_ranges(&mut self) -> PolarsResult<()> {
+        while self.workers.len() < self.threads {
+            let Some(range) = self.ranges.pop_front() else {
+                break;
+            };
+            let worker = self.start_range(range)?;
+            self.workers.push_back(worker);
+        }
+        Ok(())
+    }
+
+    // Nothing is yielded after an error: unblock the threads, then wait for them
+    fn stop(&mut self) {
+        self.ranges.clear();
+        for RangeWorker { receiver, worker } in self.workers.drain(..) {
+            drop(receiver);
+            let _ = worker.join();
+        }
+    }
+
+    // The next batch of the ranges, in file order
+    fn next_range_batch(&mut self) -> Option<PolarsResult<SasBatch>> {
+        loop {
+            if let Err(e) = self.start_ranges() {
+                return Some(Err(e));
+            }
+            match self.workers.front()?.receiver.recv() {
+                Ok(batch) => return Some(batch),
+                // The range is decoded, or its thread panicked
+                Err(_) => {
+                    let range = self.workers.pop_front()?;
+                    if range.worker.join().is_err() {
+                        return Some(Err(PolarsError::ComputeError("A thread decoding a page range panicked".into())));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl SasParallelBatchIterator {
+    /// Decode the next batch with the location of its rows in the file
+    pub fn next_sas_batch(&mut self) -> Option<PolarsResult<SasBatch>> {
+        if let Some(sequential) = &mut self.sequential {
+            return sequential.next_sas_batch();
+        }
+        loop {
+            if self.rows >= self.rows_read.end {
+                self.stop();
+                return None;
+            }
+            let mut batch = match self.next_range_batch()? {
+                Ok(batch) => batch,
+                Err(e) => {
+                    self.stop();
+                    return Some(Err(e));
+                }
+            };
+
+            let first_row = self.rows;
+            self.rows += batch.df.height() as u64;
+            if self.rows <= self.rows_read.start {
+                continue;
+            }
+            // Only the rows of `skip_rows..skip_rows + n_rows`
+            let start = first_row.max(self.rows_read.start);
+            let end = self.rows.min(self.rows_read.end);
+            if start > first_row || end < self.rows {
+                batch.df = batch.df.slice((start - first_row) as i64, (end - start) as usize);
+            }
+            batch.first_row = start;
+
+            if let Some(row_index) = &self.row_index {
+                let column = row_index.column(batch.first_row, batch.df.height())
+                    .and_then(|column| batch.df.insert_column(0, column).map(|_| ()));
+                if let Err(e) = column {
+                    self.stop();
+                    return Some(Err(e));
+                }
+            }
+            return Some(Ok(batch));
+        }
+    }
+
+    /// Iterate over the batches with the location of their rows in the file
+    pub fn sas_batches(mut self) -> impl Iterator<Item = PolarsResult<SasBatch>> {
+        std::iter::from_fn(move || self.next_sas_batch())
+    }
+}
+
+impl Iterator for SasParallelBatchIterator {
+    type Item = PolarsResult<DataFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sas_batch().map(|batch| batch.map(|batch| batch.df))
+    }
+}
+
+impl Drop for SasParallelBatchIterator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// The data pages split in `RANGES_PER_THREAD` ranges per thread
+fn page_ranges(reader: &SasReader, threads: usize) -> PolarsResult<VecDeque<(u64, u64)>> {
+    let (first_page, page_count) = reader.page_range()?;
+    let pages = page_count.saturating_sub(first_page);
+    let range_count = (threads as u64 * RANGES_PER_THREAD).clamp(1, pages.max(1));
+    let range_length = pages.div_ceil(range_count).max(1);
+    Ok((first_page..page_count)
+        .step_by(range_length as usize)
+        .map(|start| (start, (start + range_length).min(page_count)))
+        .collect())
+}
+
+// Whether the range readers can seek to their pages: whole-file compressed inputs and
+// archive members would be decompressed from their start, remote files fetched again, by
+// every reader, and transport files have no pages
+fn seeks_pages(file_path: &str, reader: &SasReader) -> bool {
+    let path = Path::new(file_path);
+    !file_path.starts_with("http://")
+        && !file_path.starts_with("https://")
+        && archive::split_path(path).is_none()
+        && compression::detect(path).is_none()
+        && reader.file_format() == SasFileFormat::Sas7bdat
+}
@@ -0,0 +1,83 @@
+use std::fs::File;
+
+use polars::prelude::*;
+
+use crate::options::format_spec;
+use crate::{SasBatchIterator, SasMetadata, SasParquetOptions};
+
+/// Convert a SAS file to a Parquet file, one batch at a time.
+///
+/// Every batch read is written as a row group, or the batches are regrouped in row groups
+/// of `row_group_size` rows: at most one row group and one batch are held in memory. The
+/// dataset metadata is stored in the key-value metadata of the file under the `sas.` keys
+/// (`sas.dataset_name`, `sas.encoding`, ...), the label and format of a column under
+/// `sas.label.<column>` and `sas.format.<column>`.
+pub fn sas_to_parquet(input: &str, output: &str, options: SasParquetOptions) -> PolarsResult<()> {
+    let mut batches = SasBatchIterator::open(input, options.read_options)?;
+    let schema = batches.schema()?.clone();
+    let key_value_metadata = key_value_metadata(&batches.metadata()?, &schema);
+
+    let statistics = if options.statistics {
+        StatisticsOptions::default()
+    } else {
+        StatisticsOptions::empty()
+    };
+    let mut writer = ParquetWriter::new(File::create(output)?)
+        .with_compression(options.compression)
+        .with_statistics(statistics)
+        .with_key_value_metadata(Some(KeyValueMetadata::from_static(key_value_metadata)))
+        .batched(&schema)?;
+
+    // A DataFrame of one chunk per column is written as one row group
+    let mut write_row_group = |mut df: DataFrame| {
+        df.rechunk_mut();
+        writer.write_batch(&df)
+    };
+    // Rows read but not written yet, fewer than `row_group_size`
+    let mut pending: Option<DataFrame> = None;
+    for batch in batches {
+        let batch = batch?;
+        if batch.height() == 0 {
+            continue;
+        }
+        let Some(row_group_size) = options.row_group_size.filter(|&rows| rows > 0) else {
+            write_row_group(batch)?;
+            continue;
+        };
+        let mut df = match pending.take() {
+            Some(mut df) => {
+                df.vstack_mut(&batch)?;
+                df
+            }
+            None => batch,
+        };
+        while df.height() >= row_group_size {
+            write_row_group(df.slice(0, row_group_size))?;
+            df = df.slice(row_group_size as i64, df.height() - row_group_size);
+        }
+        pending = Some(df);
+    }
+    if let Some(df) = pending.filter(|df| df.height() > 0) {
+        write_row_group(df)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+// The dataset metadata, then the labels and formats of the columns written
+fn key_value_metadata(metadata: &SasMetadata, schema: &Schema) -> Vec<(String, String)> {
+    let mut key_value_metadata: Vec<(String, String)> = metadata
+        .properties()
+        .into_iter()
+        .map(|(key, value)| (format!("sas.{}", key), value))
+        .collect();
+    for column in metadata.columns.iter().filter(|column| schema.contains(&column.name)) {
+        if !column.label.is_empty() {
+            key_value_metadata.push((format!("sas.label.{}", column.name), column.label.clone()));
+        }
+        if let Some(format) = format_spec(column) {
+            key_value_metadata.push((format!("sas.format.{}", column.name), format));
+        }
+    }
+    key_value_metadata
+}
@@ -0,0 +1,86 @@
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, JoinHandle};
+
+use polars::prelude::*;
+
+use crate::{SasArrowReaderInfo, SasBatchIterator};
+
+/// Streaming iterator decoding the batches ahead on a background thread.
+///
+/// Created by `SasBatchIterator::prefetch`. The background thread decodes and converts
+/// the batches into a bounded queue, so the work done on a batch overlaps with the
+/// decoding of the next ones. Batches and errors are yielded in file order. Dropping the
+/// iterator stops the background thread after the batch it is decoding.
+pub struct SasPrefetchIterator {
+    schema: Schema,
+    info: SasArrowReaderInfo,
+    receiver: Option<Receiver<PolarsResult<DataFrame>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SasPrefetchIterator {
+    pub(crate) fn new(mut batches: SasBatchIterator, queue_size: usize) -> PolarsResult<Self> {
+        let schema = batches.schema()?.clone();
+        let info = *batches.info();
+
+        let (sender, receiver) = sync_channel(queue_size.max(1));
+        let worker = thread::Builder::new()
+            .name("sas-prefetch".to_string())
+            .spawn(move || {
+                for batch in batches {
+                    // The iterator was dropped
+                    if sender.send(batch).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(SasPrefetchIterator {
+            schema,
+            info,
+            receiver: Some(receiver),
+            worker: Some(worker),
+        })
+    }
+
+    /// Get the schema without reading any data
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Get reader info
+    pub fn info(&self) -> &SasArrowReaderInfo {
+        &self.info
+    }
+}
+
+impl Iterator for SasPrefetchIterator {
+    type Item = PolarsResult<DataFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let receiver = self.receiver.as_ref()?;
+        match receiver.recv() {
+            Ok(batch) => Some(batch),
+            Err(_) => {
+                // The background thread is done: end of data, or a panic to report
+                self.receiver = None;
+                match self.worker.take().map(JoinHandle::join) {
+                    Some(Err(_)) => Some(Err(PolarsError::ComputeError(
+                        "The background thread decoding the batches panicked".into(),
+                    ))),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SasPrefetchIterator {
+    fn drop(&mut self) {
+        // Unblock the background thread, then wait for it to release the reader
+        self.receiver = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
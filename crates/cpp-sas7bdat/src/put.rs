@@ -0,0 +1,355 @@
+use polars::prelude::*;
+
+use crate::options::format_spec;
+use crate::writer::{sas_values, SasValues};
+use crate::xport::{parse_format, EPOCH_OFFSET_DAYS};
+use crate::{SasMetadata, SasReadOptions};
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const SECONDS_PER_DAY: f64 = 86_400.0;
+// Format of the numeric columns without format
+const DEFAULT_FORMAT: &str = "BEST12.";
+
+/// Render a SAS value as the `PUT` function does with the numeric format `format`, e.g.
+/// `COMMA12.2`: right-aligned in the width of the format, `.` when `value` is missing.
+///
+/// Supported formats are `w.d` (or `Fw.d`), `BESTw.`, `COMMAw.d`, `DOLLARw.d`, `PERCENTw.d`,
+/// `Zw.d`, `DATEw.`, `MMDDYYw.`, `DATETIMEw.d` and `TIMEw.d`. Dates are days and datetimes
+/// seconds since 1960-01-01, times are seconds. Numbers too wide for their format are rendered
+/// with `BESTw.`, then as `*`s.
+pub fn sas_put(value: Option<f64>, format: &str) -> PolarsResult<String> {
+    Ok(PutFormat::parse(format)?.put(value))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Fixed,
+    Best,
+    Comma,
+    Dollar,
+    Percent,
+    Z,
+    Date,
+    Mmddyy,
+    Datetime,
+    Time,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PutFormat {
+    kind: Kind,
+    width: usize,
+    decimals: usize,
+}
+
+impl PutFormat {
+    pub(crate) fn parse(format: &str) -> PolarsResult<Self> {
+        let (name, width, decimals) = parse_format(format);
+        // Default width and the widths allowed
+        let (kind, default_width, widths) = match name.to_ascii_uppercase().as_str() {
+            "" if width > 0 => (Kind::Fixed, 0, 1..=32),
+            "F" => (Kind::Fixed, 12, 1..=32),
+            "BEST" => (Kind::Best, 12, 1..=32),
+            "COMMA" => (Kind::Comma, 6, 1..=32),
+            "DOLLAR" => (Kind::Dollar, 6, 2..=32),
+            "PERCENT" => (Kind::Percent, 6, 4..=32),
+            "Z" => (Kind::Z, 1, 1..=32),
+            "DATE" => (Kind::Date, 7, 5..=11),
+            "MMDDYY" => (Kind::Mmddyy, 8, 2..=10),
+            "DATETIME" => (Kind::Datetime, 16, 7..=40),
+            "TIME" => (Kind::Time, 8, 2..=20),
+            _ => {
+                return Err(PolarsError::ComputeError(format!("Unsupported SAS format {}", format).into()));
+            }
+        };
+        let width = if width == 0 { default_width } else { width as usize };
+        let decimals = match kind {
+            Kind::Date | Kind::Mmddyy | Kind::Best => 0,
+            _ => decimals as usize,
+        };
+        if !widths.contains(&width) || (decimals > 0 && decimals >= width) {
+            return Err(PolarsError::ComputeError(format!("Invalid width of the SAS format {}", format).into()));
+        }
+        Ok(PutFormat { kind, width, decimals })
+    }
+
+    pub(crate) fn put(&self, value: Option<f64>) -> String {
+        let Some(value) = value.filter(|value| value.is_finite()) else {
+            return format!("{:>width$}", ".", width = self.width);
+        };
+        let text = match self.kind {
+            Kind::Fixed => Some(signed(value, fixed(value.abs(), self.decimals))),
+            Kind::Best => best(value, self.width),
+            Kind::Comma => Some(signed(value, comma(value.abs(), self.decimals))),
+            Kind::Dollar => Some(signed(value, format!("${}", comma(value.abs(), self.decimals)))),
+            // Negative percentages are in parentheses, a blank stands for the closing one
+            Kind::Percent => match fixed(value.abs() * 100.0, self.decimals) {
+                percent if value < 0.0 => Some(format!("({}%)", percent)),
+                percent => Some(format!("{}% ", percent)),
+            },
+            Kind::Z => {
+                let digits = fixed(value.abs(), self.decimals);
+                let zeros = self.width.saturating_sub(digits.len() + usize::from(value < 0.0));
+                Some(signed(value, format!("{}{}", "0".repeat(zeros), digits)))
+            }
+            Kind::Date => date(value, self.width),
+            Kind::Mmddyy => mmddyy(value, self.width),
+            Kind::Datetime => datetime(value, self.width, self.decimals),
+            Kind::Time => time(value, self.width, self.decimals),
+        };
+        let temporal = matches!(self.kind, Kind::Date | Kind::Mmddyy | Kind::Datetime | Kind::Time);
+        let text = match text {
+            Some(text) if text.len() <= self.width => Some(text),
+            _ if temporal => None,
+            _ => best(value, self.width),
+        };
+        match text {
+            Some(text) => format!("{:>width$}", text, width = self.width),
+            None => "*".repeat(self.width),
+        }
+    }
+}
+
+fn signed(value: f64, digits: String) -> String {
+    match value < 0.0 && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        true => format!("-{}", digits),
+        false => digits,
+    }
+}
+
+// The integer and fractional digits of `value` (not negative) rounded half away from zero
+// to `decimals` decimals, forgiving the error of the binary representation
+fn digits(value: f64, decimals: usize) -> (String, String) {
+    let scaled = value * 10f64.powi(decimals as i32);
+    let rounded = (scaled * (1.0 + 4.0 * f64::EPSILON)).round();
+    let digits = format!("{:0>width$.0}", rounded, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    (integer.to_string(), fraction.to_string())
+}
+
+fn fixed(value: f64, decimals: usize) -> String {
+    match digits(value, decimals) {
+        (integer, fraction) if fraction.is_empty() => integer,
+        (integer, fraction) => format!("{}.{}", integer, fraction),
+    }
+}
+
+fn comma(value: f64, decimals: usize) -> String {
+    let (integer, fraction) = digits(value, decimals);
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    match fraction.is_empty() {
+        true => grouped,
+        false => format!("{}.{}", grouped, fraction),
+    }
+}
+
+// Digits without trailing zeros in the fraction, and the count of their significant digits
+fn shortest(value: f64, decimals: usize) -> (String, usize) {
+    let (integer, fraction) = digits(value, decimals);
+    let fraction = fraction.trim_end_matches('0');
+    let text = match fraction.is_empty() {
+        true => integer,
+        false => format!("{}.{}", integer, fraction),
+    };
+    let significant = text.trim_matches(['0', '.']).chars().filter(char::is_ascii_digit).count();
+    (text, significant)
+}
+
+// `magnitude` as a mantissa of `shift + 1` integer digits, with decimals when there is one
+// digit, times a power of 10
+fn scientific(magnitude: f64, shift: usize, available: usize) -> Option<(String, usize)> {
+    let mut exponent = magnitude.log10().floor() as i32 - shift as i32;
+    for _ in 0..2 {
+        let suffix = format!("E{}", exponent);
+        let room = available.checked_sub(suffix.len()).filter(|&room| room > shift)?;
+        let decimals = if shift == 0 { room.saturating_sub(2) } else { 0 };
+        let (mantissa, significant) = shortest(magnitude / 10f64.powi(exponent), decimals);
+        // Rounded up to one more digit
+        if mantissa.split('.').next().unwrap_or_default().len() > shift + 1 {
+            exponent += 1;
+            continue;
+        }
+        return Some((format!("{}{}", mantissa, suffix), significant));
+    }
+    None
+}
+
+// The most precise of the decimal and the scientific notations fitting in `width`, the
+// decimal one first
+fn best(value: f64, width: usize) -> Option<String> {
+    if value == 0.0 {
+        return Some("0".to_string());
+    }
+    let available = width.checked_sub(usize::from(value < 0.0)).filter(|&n| n > 0)?;
+    let magnitude = value.abs();
+
+    // Fractions lose their leading zero in 2 characters
+    let integer_length = digits(magnitude, 0).0.len();
+    let decimal = (integer_length <= available)
+        .then(|| {
+            let decimals = match integer_length == 1 && magnitude < 1.0 && available == 2 {
+                true => 1,
+                false => available.saturating_sub(integer_length + 1),
+            };
+            (0..=decimals).rev().map(|decimals| shortest(magnitude, decimals)).find_map(|(text, significant)| {
+                let text = match text.strip_prefix('0') {
+                    Some(fraction) if text.len() > available && fraction.starts_with('.') => fraction.to_string(),
+                    _ => text,
+                };
+                (text.len() <= available).then_some((text, significant))
+            })
+        })
+        .flatten()
+        .filter(|(_, significant)| *significant > 0);
+    // Mantissas of several digits when a single one leaves no room for decimals
+    let mut exponents: Vec<_> = scientific(magnitude, 0, available).into_iter().collect();
+    if exponents.iter().all(|(text, _)| !text.contains('.')) {
+        exponents.extend((1..available).filter_map(|shift| scientific(magnitude, shift, available)));
+    }
+    let candidates = decimal.into_iter().chain(exponents);
+    let (text, _) = candidates.fold(None, |best: Option<(String, usize)>, candidate| match best {
+        Some(best) if best.1 >= candidate.1 => Some(best),
+        _ => Some(candidate),
+    })?;
+    Some(signed(value, text))
+}
+
+// Year, month (1-12) and day of the SAS date `days`
+fn civil(days: f64) -> Option<(i64, usize, i64)> {
+    if !(-1e9..1e9).contains(&days) {
+        return None;
+    }
+    // Days since 0000-03-01, by eras of 400 years
+    let days = (days.floor() - EPOCH_OFFSET_DAYS) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (0..=9999).contains(&year).then_some((year, month as usize, day))
+}
+
+fn date(days: f64, width: usize) -> Option<String> {
+    let (year, month, day) = civil(days)?;
+    let month = MONTHS[month - 1];
+    Some(match width {
+        5 | 6 => format!("{:02}{}", day, month),
+        7 | 8 => format!("{:02}{}{:02}", day, month, year % 100),
+        9 | 10 => format!("{:02}{}{:04}", day, month, year),
+        _ => format!("{:02}-{}-{:04}", day, month, year),
+    })
+}
+
+fn mmddyy(days: f64, width: usize) -> Option<String> {
+    let (year, month, day) = civil(days)?;
+    Some(match width {
+        2 | 3 => format!("{:02}", month),
+        4 => format!("{:02}{:02}", month, day),
+        5 => format!("{:02}/{:02}", month, day),
+        6 | 7 => format!("{:02}{:02}{:02}", month, day, year % 100),
+        8 | 9 => format!("{:02}/{:02}/{:02}", month, day, year % 100),
+        _ => format!("{:02}/{:02}/{:04}", month, day, year),
+    })
+}
+
+// Hours, minutes and seconds of `seconds` (not negative), the seconds truncated to `decimals`
+fn clock(seconds: f64, decimals: usize) -> (i64, i64, String) {
+    let scale = 10f64.powi(decimals as i32);
+    let truncated = ((seconds * scale * (1.0 + 4.0 * f64::EPSILON)).floor() / scale).max(0.0);
+    let whole = truncated.floor() as i64;
+    let seconds = match decimals {
+        0 => format!("{:02}", whole % 60),
+        _ => format!("{:0width$.decimals$}", truncated % 60.0, width = decimals + 3),
+    };
+    (whole / 3600, whole / 60 % 60, seconds)
+}
+
+fn datetime(seconds: f64, width: usize, decimals: usize) -> Option<String> {
+    // Width without the decimals, 4-digit years need 19
+    let width = if decimals > 0 { width - decimals - 1 } else { width };
+    let days = (seconds / SECONDS_PER_DAY).floor();
+    let date = date(days, if width >= 19 { 9 } else { 7 })?;
+    let (hours, minutes, seconds) = clock(seconds - days * SECONDS_PER_DAY, decimals);
+    Some(match width {
+        0..=9 => date,
+        10..=12 => format!("{}:{:02}", date, hours),
+        13..=15 => format!("{}:{:02}:{:02}", date, hours, minutes),
+        _ => format!("{}:{:02}:{:02}:{}", date, hours, minutes, seconds),
+    })
+}
+
+fn time(seconds: f64, width: usize, decimals: usize) -> Option<String> {
+    let (hours, minutes, rest) = clock(seconds.abs(), decimals);
+    let width = if decimals > 0 { width - decimals - 1 } else { width };
+    let text = match width {
+        0..=4 => format!("{}", hours),
+        5..=7 => format!("{}:{:02}", hours, minutes),
+        _ => format!("{}:{:02}:{}", hours, minutes, rest),
+    };
+    Some(if seconds < 0.0 { format!("-{}", text) } else { text })
+}
+
+// Columns read as the strings of their format, see `SasReadOptions::with_formatted_column`
+pub(crate) struct FormattedColumns {
+    // Column names and the format given for them
+    requested: Vec<(String, Option<String>)>,
+    columns: Vec<(PlSmallStr, PutFormat)>,
+}
+
+impl FormattedColumns {
+    pub(crate) fn new(options: &SasReadOptions) -> Self {
+        FormattedColumns {
+            requested: options.formatted_columns.clone(),
+            columns: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.requested.is_empty()
+    }
+
+    // Parse the format of the columns and read them as strings in `schema`
+    pub(crate) fn bind(&mut self, metadata: &SasMetadata, schema: &mut Schema) -> PolarsResult<()> {
+        for (name, format) in &self.requested {
+            let column = metadata.columns.iter().find(|column| column.name == *name);
+            let (Some(column), Some(dtype)) = (column, schema.get(name)) else {
+                return Err(PolarsError::ColumnNotFound(format!("No column '{}' to format", name).into()));
+            };
+            if matches!(dtype, DataType::String | DataType::Categorical(..)) {
+                return Err(PolarsError::ComputeError(
+                    format!("Cannot format the character column '{}'", name).into(),
+                ));
+            }
+            let format = format.clone().or_else(|| format_spec(column));
+            let format = PutFormat::parse(format.as_deref().unwrap_or(DEFAULT_FORMAT)).map_err(|e| {
+                PolarsError::ComputeError(format!("Cannot format the column '{}': {}", name, e).into())
+            })?;
+            self.columns.push((name.as_str().into(), format));
+            schema.with_column(name.as_str().into(), DataType::String);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply(&self, df: &mut DataFrame) -> PolarsResult<()> {
+        for (name, format) in &self.columns {
+            let SasValues::Numbers(values, _) = sas_values(df.column(name)?)? else {
+                unreachable!("character columns are not formatted");
+            };
+            let strings: StringChunked = values
+                .iter()
+                .map(|value| Some(format.put(value).trim_start().to_string()))
+                .collect();
+            df.with_column(strings.with_name(name.clone()).into_column())?;
+        }
+        Ok(())
+    }
+}
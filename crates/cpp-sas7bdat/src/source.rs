@@ -0,0 +1,67 @@
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::os::raw::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::SasArrowDataSource;
+
+// State behind the user_data pointer of a callback data source
+struct CallbackState<R> {
+    source: R,
+    // Stream position of the first byte of the SAS data
+    start: u64,
+    eof: bool,
+}
+
+/// Wrap a Rust byte stream into C callbacks. The returned data source owns `source`,
+/// it is dropped by the `release` callback.
+pub(crate) fn callback_source<R: Read + Seek + Send + 'static>(source: R, start: u64) -> SasArrowDataSource {
+    let state = Box::new(CallbackState { source, start, eof: false });
+    SasArrowDataSource {
+        user_data: Box::into_raw(state) as *mut c_void,
+        read: Some(read_callback::<R>),
+        seek: Some(seek_callback::<R>),
+        eof: Some(eof_callback::<R>),
+        release: Some(release_callback::<R>),
+    }
+}
+
+unsafe extern "C" fn read_callback<R: Read>(user_data: *mut c_void, buffer: *mut c_void, length: u64) -> i64 {
+    let state = &mut *(user_data as *mut CallbackState<R>);
+    let buffer = std::slice::from_raw_parts_mut(buffer as *mut u8, length as usize);
+    // Never unwind into C++
+    let result = catch_unwind(AssertUnwindSafe(|| loop {
+        match state.source.read(buffer) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }));
+    match result {
+        Ok(Ok(0)) => {
+            state.eof = true;
+            0
+        }
+        Ok(Ok(n)) => n as i64,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn seek_callback<R: Seek>(user_data: *mut c_void, offset: u64) -> i32 {
+    let state = &mut *(user_data as *mut CallbackState<R>);
+    let position = state.start + offset;
+    match catch_unwind(AssertUnwindSafe(|| state.source.seek(SeekFrom::Start(position)))) {
+        Ok(Ok(_)) => {
+            state.eof = false;
+            0
+        }
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn eof_callback<R>(user_data: *mut c_void) -> bool {
+    let state = &*(user_data as *const CallbackState<R>);
+    state.eof
+}
+
+unsafe extern "C" fn release_callback<R>(user_data: *mut c_void) {
+    drop(Box::from_raw(user_data as *mut CallbackState<R>));
+}
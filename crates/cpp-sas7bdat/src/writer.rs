@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::mem;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use polars::prelude::*;
+
+use crate::compressors::{rdc_compress, rle_compress};
+use crate::xport::{parse_format, EPOCH_OFFSET_DAYS, EPOCH_OFFSET_SECONDS};
+use crate::{SasCompression, SasWriteOptions};
+
+// Layout of a 64-bit little-endian file
+const HEADER_LENGTH: usize = 8192;
+const MAGIC_NUMBER: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc2, 0xea, 0x81, 0x60,
+    0xb3, 0x14, 0x11, 0xcf, 0xbd, 0x92, 0x08, 0x00, 0x09, 0xc7, 0x31, 0x8c, 0x18, 0x1f, 0x10, 0x11,
+];
+// Page type, row (block) count and subheader count follow 32 bytes of page information
+const PAGE_HEADER_LENGTH: usize = 40;
+const POINTER_LENGTH: usize = 24;
+const PAGE_META: u16 = 0;
+const PAGE_DATA: u16 = 256;
+const MIN_PAGE_LENGTH: usize = 1024;
+// The offsets of the column texts are 16-bit
+const MAX_SUBHEADER_LENGTH: usize = 32_000;
+// Compression and type of the pointer to a compressed row, type only for a row stored as is
+const COMPRESSED_ROW: u8 = 4;
+const ROW_TYPE: u8 = 1;
+
+const ROW_SIZE_SIGNATURE: [u8; 8] = [0xF7, 0xF7, 0xF7, 0xF7, 0x00, 0x00, 0x00, 0x00];
+const COLUMN_SIZE_SIGNATURE: [u8; 8] = [0xF6, 0xF6, 0xF6, 0xF6, 0x00, 0x00, 0x00, 0x00];
+const COLUMN_TEXT_SIGNATURE: [u8; 8] = [0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+const COLUMN_NAME_SIGNATURE: [u8; 8] = [0xFF; 8];
+const COLUMN_ATTRIBUTES_SIGNATURE: [u8; 8] = [0xFC, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+const FORMAT_AND_LABEL_SIGNATURE: [u8; 8] = [0xFE, 0xFB, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+const COLUMN_LIST_SIGNATURE: [u8; 8] = [0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+const ROW_SIZE_LENGTH: usize = 808;
+const FORMAT_AND_LABEL_LENGTH: usize = 72;
+// Procedure named in the first text subheader
+const CREATOR_PROC: &str = "DATASTEP";
+
+// The SAS missing value `.`: a NaN with the complement of the tag in its 6th byte
+const MISSING: u64 = 0x7FF8_D100_0000_0000;
+
+const NAME_LIMIT: usize = 32;
+const LABEL_LIMIT: usize = 256;
+const STRING_LIMIT: usize = 32767;
+
+/// Values of a column as stored in a SAS file
+pub(crate) enum SasValues {
+    /// SAS numbers (days or seconds for the temporal types) with the default format
+    Numbers(Float64Chunked, &'static str),
+    Strings(StringChunked),
+}
+
+/// Convert a column to the values stored in a SAS file: numeric, boolean and decimal
+/// columns as numbers, date, datetime and time columns as days/seconds since 1960-01-01
+/// with the `DATE9.`, `DATETIME20.` and `TIME8.` formats, string and categorical columns
+/// as strings.
+pub(crate) fn sas_values(column: &Column) -> PolarsResult<SasValues> {
+    let (numbers, default_format) = match column.dtype() {
+        DataType::String | DataType::Categorical(..) | DataType::Enum(..) => {
+            return Ok(SasValues::Strings(column.cast(&DataType::String)?.str()?.clone()));
+        }
+        DataType::Date => {
+            let days = column.cast(&DataType::Int32)?.cast(&DataType::Float64)?;
+            (days.f64()? + EPOCH_OFFSET_DAYS, "DATE9.")
+        }
+        DataType::Datetime(unit, _) => {
+            let per_second = match unit {
+                TimeUnit::Milliseconds => 1e3,
+                TimeUnit::Microseconds => 1e6,
+                TimeUnit::Nanoseconds => 1e9,
+            };
+            let values = column.cast(&DataType::Int64)?.cast(&DataType::Float64)?;
+            (values.f64()? / per_second + EPOCH_OFFSET_SECONDS, "DATETIME20.")
+        }
+        DataType::Time => {
+            let nanoseconds = column.cast(&DataType::Int64)?.cast(&DataType::Float64)?;
+            (nanoseconds.f64()? / 1e9, "TIME8.")
+        }
+        DataType::Boolean
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Decimal(..) => (column.cast(&DataType::Float64)?.f64()?.clone(), ""),
+        dtype => {
+            return Err(PolarsError::ComputeError(
+                format!("Cannot write the column '{}' of type {} to a SAS file", column.name(), dtype).into(),
+            ));
+        }
+    };
+    Ok(SasValues::Numbers(numbers, default_format))
+}
+
+/// A field of `width` bytes: the text padded with blanks
+pub(crate) fn field(text: &str, width: usize) -> Vec<u8> {
+    let mut bytes = text.as_bytes()[..text.len().min(width)].to_vec();
+    bytes.resize(width, b' ');
+    bytes
+}
+
+/// The longest prefix of `text` of at most `limit` bytes ending on a character boundary
+pub(crate) fn truncated(text: &str, limit: usize) -> &str {
+    let mut end = text.len().min(limit);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+fn put_u16(bytes: &mut [u8], offset: usize, value: u16) {
+    bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+// A subheader of `length` bytes (rounded up to 8) starting with its signature
+fn subheader(signature: [u8; 8], length: usize) -> Vec<u8> {
+    let mut subheader = vec![0u8; length.next_multiple_of(8)];
+    subheader[..8].copy_from_slice(&signature);
+    subheader
+}
+
+// The current time in seconds since 1960-01-01
+fn sas_now() -> f64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64());
+    now.floor() + EPOCH_OFFSET_SECONDS
+}
+
+// Location of a text in the column text subheaders: subheader index, offset, length
+type TextRef = (u16, u16, u16);
+
+// The column names, formats and labels, split in blocks of at most `limit` bytes
+struct ColumnTexts {
+    blocks: Vec<Vec<u8>>,
+    limit: usize,
+}
+
+impl ColumnTexts {
+    fn new(limit: usize, compression: SasCompression) -> Self {
+        // Block length, compression (blanks: none) and the creator procedure, 8 bytes
+        // further when compressed
+        let mut first = vec![0u8; 12];
+        first.extend(match compression {
+            SasCompression::None => field("", 16),
+            SasCompression::Rle => field("SASYZCRL", 24),
+            SasCompression::Rdc => field("SASYZCR2", 24),
+        });
+        first.extend(field(CREATOR_PROC, 8));
+        ColumnTexts { blocks: vec![first], limit }
+    }
+
+    fn add(&mut self, text: &str) -> TextRef {
+        if text.is_empty() {
+            return (0, 0, 0);
+        }
+        let padded = text.len().next_multiple_of(4);
+        if self.blocks.last().map_or(0, Vec::len) + padded > self.limit {
+            self.blocks.push(vec![0u8; 4]);
+        }
+        let index = self.blocks.len() - 1;
+        let block = &mut self.blocks[index];
+        let offset = block.len();
+        block.extend(field(text, padded));
+        (index as u16, offset as u16, text.len() as u16)
+    }
+
+    // The text subheaders: the signature followed by the block, its length first
+    fn subheaders(self) -> Vec<Vec<u8>> {
+        self.blocks.into_iter().map(|mut block| {
+            let length = block.len() as u16;
+            put_u16(&mut block, 0, length);
+            let mut subheader = subheader(COLUMN_TEXT_SIGNATURE, 8 + block.len());
+            subheader[8..8 + block.len()].copy_from_slice(&block);
+            subheader
+        }).collect()
+    }
+}
+
+fn put_text(bytes: &mut [u8], offset: usize, (index, text_offset, length): TextRef) {
+    put_u16(bytes, offset, index);
+    put_u16(bytes, offset + 2, text_offset);
+    put_u16(bytes, offset + 4, length);
+}
+
+// A metadata page holding the subheaders, stored from the end of the page
+fn meta_page(subheaders: &[Vec<u8>], page_length: usize) -> Vec<u8> {
+    let mut page = vec![0u8; page_length];
+    put_u16(&mut page, 32, PAGE_META);
+    put_u16(&mut page, 34, subheaders.len() as u16);
+    put_u16(&mut page, 36, subheaders.len() as u16);
+    let mut end = page_length;
+    for (i, subheader) in subheaders.iter().enumerate() {
+        end -= subheader.len();
+        page[end..end + subheader.len()].copy_from_slice(subheader);
+        let pointer = PAGE_HEADER_LENGTH + i * POINTER_LENGTH;
+        put_u64(&mut page, pointer, end as u64);
+        put_u64(&mut page, pointer + 8, subheader.len() as u64);
+    }
+    page
+}
+
+struct WriterColumn {
+    name: String,
+    label: String,
+    format: (String, u32, u32),
+    numeric: bool,
+    // Position of the value in the row, and its length in bytes
+    offset: usize,
+    length: usize,
+}
+
+/// Writer of a 64-bit little-endian `.sas7bdat` file, written batch by batch.
+///
+/// The columns are those of the first batch. Numbers are stored as 8-byte doubles, the
+/// temporal types as SAS dates, datetimes and times with the `DATE9.`, `DATETIME20.` and
+/// `TIME8.` formats, and strings with the length of the longest value of the first batch
+/// unless reserved with `SasWriteOptions::with_string_length`. The metadata pages come
+/// first and are written again by `finish` with the row count, which must be called for
+/// the file to be complete. Compressed rows (see `SasWriteOptions::with_compression`) are
+/// stored as subheaders of the pages after the metadata.
+pub struct SasWriter {
+    out: BufWriter<File>,
+    options: SasWriteOptions,
+    schema: Option<Schema>,
+    columns: Vec<WriterColumn>,
+    row_length: usize,
+    page_length: usize,
+    meta_pages: u64,
+    // Data page being filled and its row count
+    page: Vec<u8>,
+    page_rows: usize,
+    // Start of the compressed rows, stored from the end of the page
+    page_end: usize,
+    data_pages: u64,
+    row_count: u64,
+    created: f64,
+}
+
+impl SasWriter {
+    /// Create the file `file_path`, the columns are set by the first batch
+    pub fn create(file_path: &str, options: SasWriteOptions) -> PolarsResult<Self> {
+        Ok(SasWriter {
+            out: BufWriter::new(File::create(file_path)?),
+            options,
+            schema: None,
+            columns: Vec::new(),
+            row_length: 0,
+            page_length: 0,
+            meta_pages: 0,
+            page: Vec::new(),
+            page_rows: 0,
+            page_end: 0,
+            data_pages: 0,
+            row_count: 0,
+            created: sas_now(),
+        })
+    }
+
+    // `text` if it fits in `limit` bytes, truncated or an error otherwise
+    fn check<'a>(&self, text: &'a str, limit: usize, what: &str) -> PolarsResult<&'a str> {
+        if text.len() <= limit {
+            return Ok(text);
+        }
+        if self.options.truncate {
+            return Ok(truncated(text, limit));
+        }
+        let shown = truncated(text, 64);
+        Err(PolarsError::ComputeError(
+            format!(
+                "The {} '{}{}' is longer than {} bytes (enable truncation to shorten it)",
+                what, shown, if shown.len() < text.len() { "..." } else { "" }, limit
+            ).into(),
+        ))
+    }
+
+    fn lookup<'a, T>(pairs: &'a [(String, T)], name: &str) -> Option<&'a T> {
+        pairs.iter().rev().find(|(column, _)| column == name).map(|(_, value)| value)
+    }
+
+    // Set the columns from the first batch and write the header and the metadata pages
+    fn start(&mut self, df: &DataFrame) -> PolarsResult<()> {
+        if df.width() == 0 {
+            return Err(PolarsError::ComputeError("Cannot write a SAS file without columns".into()));
+        }
+        let mut names = HashSet::new();
+        let mut columns = Vec::with_capacity(df.width());
+        for column in df.get_columns() {
+            let name = column.name().as_str();
+            let (numeric, length, default_format) = match sas_values(column)? {
+                SasValues::Numbers(_, default_format) => (true, 8, default_format),
+                SasValues::Strings(strings) => {
+                    let length = match Self::lookup(&self.options.string_lengths, name) {
+                        Some(length) => *length as usize,
+                        None => strings.into_iter().flatten().map(str::len).max().unwrap_or(0),
+                    };
+                    if length > STRING_LIMIT && !self.options.truncate {
+                        return Err(PolarsError::ComputeError(
+                            format!("The values of the column '{}' are longer than {} bytes", name, STRING_LIMIT).into(),
+                        ));
+                    }
+                    (false, length.clamp(1, STRING_LIMIT), "")
+                }
+            };
+
+            let label = Self::lookup(&self.options.labels, name).map_or("", String::as_str);
+            let format = Self::lookup(&self.options.formats, name).map_or(default_format, String::as_str);
+            let column = WriterColumn {
+                name: self.check(name, NAME_LIMIT, "column name")?.to_string(),
+                label: self.check(label, LABEL_LIMIT, "label")?.to_string(),
+                format: parse_format(format),
+                numeric,
+                offset: 0,
+                length,
+            };
+            if column.name.is_empty() || !names.insert(column.name.to_uppercase()) {
+                return Err(PolarsError::Duplicate(
+                    format!("Duplicate or empty column name '{}' in the SAS file", column.name).into(),
+                ));
+            }
+            columns.push(column);
+        }
+
+        // Numbers first, as SAS lays out the rows
+        let mut offset = 0;
+        for numeric in [true, false] {
+            for column in columns.iter_mut().filter(|column| column.numeric == numeric) {
+                column.offset = offset;
+                offset += column.length;
+            }
+        }
+        self.row_length = offset;
+        self.page_length = (self.options.page_length as usize)
+            .max(MIN_PAGE_LENGTH)
+            .max((PAGE_HEADER_LENGTH + POINTER_LENGTH + self.row_length).next_multiple_of(MIN_PAGE_LENGTH));
+        self.columns = columns;
+        self.schema = Some(df.schema().as_ref().clone());
+        self.page = vec![0u8; self.page_length];
+        self.page_end = self.page_length;
+
+        let metadata = self.metadata()?;
+        self.meta_pages = (metadata.len() / self.page_length) as u64;
+        self.out.write_all(&self.header())?;
+        self.out.write_all(&metadata)?;
+        Ok(())
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut header = vec![0u8; HEADER_LENGTH];
+        header[..32].copy_from_slice(&MAGIC_NUMBER);
+        // 8-byte integers, no alignment padding, little-endian, Unix
+        header[32] = 0x33;
+        header[35] = 0x22;
+        header[37] = 0x01;
+        header[39] = b'1';
+        // UTF-8
+        header[70] = 20;
+        header[84..92].copy_from_slice(b"SAS FILE");
+        header[92..156].copy_from_slice(&field(&self.options.dataset_name, 64));
+        header[156..164].copy_from_slice(b"DATA    ");
+        header[164..172].copy_from_slice(&self.created.to_le_bytes());
+        header[172..180].copy_from_slice(&sas_now().to_le_bytes());
+        put_u32(&mut header, 196, HEADER_LENGTH as u32);
+        put_u32(&mut header, 200, self.page_length as u32);
+        put_u64(&mut header, 204, self.meta_pages + self.data_pages);
+        header[220..228].copy_from_slice(&field("9.0401M0", 8));
+        header[228..244].copy_from_slice(&field("X64_LIN", 16));
+        header[276..292].copy_from_slice(&field(std::env::consts::OS, 16));
+        header
+    }
+
+    // The metadata pages, with the row count written so far
+    fn metadata(&self) -> PolarsResult<Vec<u8>> {
+        let max_length = MAX_SUBHEADER_LENGTH.min(self.page_length - PAGE_HEADER_LENGTH - POINTER_LENGTH) / 8 * 8;
+        let count = self.columns.len();
+
+        let mut row_size = subheader(ROW_SIZE_SIGNATURE, ROW_SIZE_LENGTH);
+        put_u64(&mut row_size, 40, self.row_length as u64);
+        put_u64(&mut row_size, 48, self.row_count);
+        put_u64(&mut row_size, 72, count as u64);
+        put_u64(&mut row_size, 80, 0);
+        put_u64(&mut row_size, 120, ((self.page_length - PAGE_HEADER_LENGTH) / self.row_length.max(1)) as u64);
+        put_u16(&mut row_size, 706, CREATOR_PROC.len() as u16);
+
+        let mut column_size = subheader(COLUMN_SIZE_SIGNATURE, 24);
+        put_u64(&mut column_size, 8, count as u64);
+
+        let mut texts = ColumnTexts::new(max_length - 8, self.options.compression);
+        let refs: Vec<_> = self.columns.iter()
+            .map(|column| (texts.add(&column.name), texts.add(&column.format.0), texts.add(&column.label)))
+            .collect();
+
+        let mut subheaders = vec![row_size, column_size];
+        subheaders.extend(texts.subheaders());
+        for chunk in refs.chunks((max_length - 32) / 8) {
+            let mut names = subheader(COLUMN_NAME_SIGNATURE, 32 + 8 * chunk.len());
+            put_u16(&mut names, 8, (names.len() - 20) as u16);
+            for (i, (name, _, _)) in chunk.iter().enumerate() {
+                put_text(&mut names, 16 + 8 * i, *name);
+            }
+            subheaders.push(names);
+        }
+        for chunk in self.columns.chunks((max_length - 32) / 16) {
+            let mut attributes = subheader(COLUMN_ATTRIBUTES_SIGNATURE, 32 + 16 * chunk.len());
+            put_u16(&mut attributes, 8, (attributes.len() - 20) as u16);
+            for (i, column) in chunk.iter().enumerate() {
+                let entry = 16 + 16 * i;
+                put_u64(&mut attributes, entry, column.offset as u64);
+                put_u32(&mut attributes, entry + 8, column.length as u32);
+                attributes[entry + 14] = if column.numeric { 1 } else { 2 };
+            }
+            subheaders.push(attributes);
+        }
+        for (column, (_, format, label)) in self.columns.iter().zip(&refs) {
+            let mut format_and_label = subheader(FORMAT_AND_LABEL_SIGNATURE, FORMAT_AND_LABEL_LENGTH);
+            put_u16(&mut format_and_label, 32, column.format.1 as u16);
+            put_u16(&mut format_and_label, 34, column.format.2 as u16);
+            put_text(&mut format_and_label, 46, *format);
+            put_text(&mut format_and_label, 52, *label);
+            subheaders.push(format_and_label);
+        }
+        let indexes: Vec<_> = (1..=count as i16).collect();
+        for chunk in indexes.chunks((max_length - 40) / 2) {
+            let mut list = subheader(COLUMN_LIST_SIGNATURE, 40 + 2 * chunk.len());
+            put_u16(&mut list, 8, (list.len() - 8) as u16);
+            put_u16(&mut list, 16, list.len() as u16);
+            put_u16(&mut list, 28, chunk.len() as u16);
+            put_u16(&mut list, 30, chunk.len() as u16);
+            for (i, index) in chunk.iter().enumerate() {
+                list[40 + 2 * i..42 + 2 * i].copy_from_slice(&index.to_le_bytes());
+            }
+            subheaders.push(list);
+        }
+
+        // As many subheaders as fit on each page
+        let mut pages = Vec::new();
+        let mut page = Vec::new();
+        let mut used = PAGE_HEADER_LENGTH;
+        for subheader in subheaders {
+            if !page.is_empty() && used + POINTER_LENGTH + subheader.len() > self.page_length {
+                pages.extend(meta_page(&mem::take(&mut page), self.page_length));
+                used = PAGE_HEADER_LENGTH;
+            }
+            used += POINTER_LENGTH + subheader.len();
+            page.push(subheader);
+        }
+        pages.extend(meta_page(&page, self.page_length));
+        Ok(pages)
+    }
+
+    /// Append the rows of a batch, with the columns of the first one
+    pub fn write_batch(&mut self, df: &DataFrame) -> PolarsResult<()> {
+        if self.schema.is_none() {
+            self.start(df)?;
+        } else if self.schema.as_ref() != Some(df.schema().as_ref()) {
+            return Err(PolarsError::SchemaMismatch(
+                format!("The batch schema {:?} differs from the schema of the SAS file", df.schema()).into(),
+            ));
+        }
+
+        let mut values = Vec::with_capacity(self.columns.len());
+        for (column, series) in self.columns.iter().zip(df.get_columns()) {
+            let mut bytes = Vec::with_capacity(df.height() * column.length);
+            match sas_values(series)? {
+                SasValues::Numbers(numbers, _) => {
+                    for value in &numbers {
+                        let value = value.filter(|v| !v.is_nan()).map_or(MISSING, f64::to_bits);
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+                SasValues::Strings(strings) => {
+                    for value in strings.into_iter() {
+                        let value = value.unwrap_or("");
+                        let what = format!("value of the column '{}'", column.name);
+                        bytes.extend(field(self.check(value, column.length, &what)?, column.length));
+                    }
+                }
+            }
+            values.push(bytes);
+        }
+
+        let mut row = vec![0u8; self.row_length];
+        for i in 0..df.height() {
+            for (column, bytes) in self.columns.iter().zip(&values) {
+                row[column.offset..column.offset + column.length]
+                    .copy_from_slice(&bytes[i * column.length..(i + 1) * column.length]);
+            }
+            self.add_row(&row)?;
+        }
+        self.row_count += df.height() as u64;
+        Ok(())
+    }
+
+    fn add_row(&mut self, row: &[u8]) -> PolarsResult<()> {
+        let compressed = match self.options.compression {
+            SasCompression::None => {
+                let start = PAGE_HEADER_LENGTH + self.page_rows * self.row_length;
+                self.page[start..start + row.len()].copy_from_slice(row);
+                self.page_rows += 1;
+                if self.page_rows == (self.page_length - PAGE_HEADER_LENGTH) / self.row_length.max(1) {
+                    self.write_page()?;
+                }
+                return Ok(());
+            }
+            SasCompression::Rle => rle_compress(row),
+            SasCompression::Rdc => rdc_compress(row),
+        };
+        // Stored as it is if it does not shrink, pointed to from the start of the page like
+        // the metadata subheaders
+        let (bytes, compression) = if compressed.len() < row.len() {
+            (compressed.as_slice(), COMPRESSED_ROW)
+        } else {
+            (row, 0)
+        };
+        let full = PAGE_HEADER_LENGTH + (self.page_rows + 1) * POINTER_LENGTH + bytes.len() > self.page_end;
+        if full || self.page_rows == u16::MAX as usize {
+            self.write_page()?;
+        }
+        let pointer = PAGE_HEADER_LENGTH + self.page_rows * POINTER_LENGTH;
+        self.page_end -= bytes.len();
+        self.page[self.page_end..self.page_end + bytes.len()].copy_from_slice(bytes);
+        put_u64(&mut self.page, pointer, self.page_end as u64);
+        put_u64(&mut self.page, pointer + 8, bytes.len() as u64);
+        self.page[pointer + 16] = compression;
+        self.page[pointer + 17] = ROW_TYPE;
+        self.page_rows += 1;
+        Ok(())
+    }
+
+    fn write_page(&mut self) -> PolarsResult<()> {
+        if self.options.compression == SasCompression::None {
+            put_u16(&mut self.page, 32, PAGE_DATA);
+        } else {
+            put_u16(&mut self.page, 32, PAGE_META);
+            put_u16(&mut self.page, 36, self.page_rows as u16);
+        }
+        put_u16(&mut self.page, 34, self.page_rows as u16);
+        self.out.write_all(&self.page)?;
+        self.page.fill(0);
+        self.page_rows = 0;
+        self.page_end = self.page_length;
+        self.data_pages += 1;
+        Ok(())
+    }
+
+    /// Write the last page and the row count, the file is complete
+    pub fn finish(mut self) -> PolarsResult<()> {
+        if self.schema.is_none() {
+            return Err(PolarsError::ComputeError("No batch written, the columns of the SAS file are unknown".into()));
+        }
+        if self.page_rows > 0 {
+            self.write_page()?;
+        }
+        let metadata = self.metadata()?;
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&self.header())?;
+        self.out.write_all(&metadata)?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Schema of the written batches, known after the first one
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+}
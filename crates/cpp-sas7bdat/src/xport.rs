@@ -0,0 +1,719 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use polars::prelude::*;
+
+use crate::archive::ReadSeek;
+use crate::labels;
+use crate::writer::{field, sas_values, truncated, SasValues};
+use crate::{
+    SasColumnMetadata, SasFileFormat, SasMetadata, SasReadOptions, SasTemporalType, SasXportVersion,
+    SasXportWriteOptions,
+};
+
+/// Start of the first record of a transport file, followed by `RARY` (V5) or `V8` (V8/V9)
+pub(crate) const MAGIC: &[u8] = b"HEADER RECORD*******LIB";
+
+const RECORD_LENGTH: usize = 80;
+
+// Days and seconds between the SAS (1960-01-01) and Unix epochs
+pub(crate) const EPOCH_OFFSET_DAYS: f64 = 3653.0;
+pub(crate) const EPOCH_OFFSET_SECONDS: f64 = 315_619_200.0;
+
+type Record = [u8; RECORD_LENGTH];
+
+// `HEADER RECORD*******<name> HEADER RECORD!!!!!!!<6 numbers of 5 digits>`
+struct HeaderRecord {
+    name: String,
+    numbers: [usize; 6],
+}
+
+fn parse_header(record: &Record) -> Option<HeaderRecord> {
+    if !record.starts_with(b"HEADER RECORD*******") || &record[28..48] != b"HEADER RECORD!!!!!!!" {
+        return None;
+    }
+    let mut numbers = [0; 6];
+    for (i, number) in numbers.iter_mut().enumerate() {
+        // Left-aligned counts (`LABELV8`) are padded with blanks
+        let digits = record[48 + 5 * i..53 + 5 * i].iter()
+            .skip_while(|&&b| b == b' ')
+            .take_while(|b| b.is_ascii_digit())
+            .fold(0, |n, &b| n * 10 + (b - b'0') as usize);
+        *number = digits;
+    }
+    Some(HeaderRecord { name: text(&record[20..28]), numbers })
+}
+
+// A text field without its trailing blanks: UTF-8 when valid, Latin-1 otherwise
+pub(crate) fn text(bytes: &[u8]) -> String {
+    let end = bytes.iter().rposition(|&b| b != b' ' && b != 0).map_or(0, |i| i + 1);
+    let bytes = &bytes[..end];
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+fn read_bytes(source: &mut impl Read, length: usize) -> PolarsResult<Vec<u8>> {
+    let mut bytes = vec![0u8; length];
+    source.read_exact(&mut bytes).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => PolarsError::ComputeError("Truncated XPORT file header".into()),
+        _ => e.into(),
+    })?;
+    Ok(bytes)
+}
+
+fn read_record(source: &mut impl Read) -> PolarsResult<Record> {
+    let mut record = [0u8; RECORD_LENGTH];
+    record.copy_from_slice(&read_bytes(source, RECORD_LENGTH)?);
+    Ok(record)
+}
+
+fn be16(bytes: &[u8]) -> usize {
+    u16::from_be_bytes([bytes[0], bytes[1]]) as usize
+}
+
+/// Convert an IBM System/370 double (big-endian, truncated to its first `bytes.len()`
+/// bytes) to an IEEE double, None for a SAS missing value (`.`, `._`, `.A`-`.Z`)
+pub(crate) fn ibm_to_f64(bytes: &[u8]) -> Option<f64> {
+    let mut raw = [0u8; 8];
+    let length = bytes.len().min(8);
+    raw[..length].copy_from_slice(&bytes[..length]);
+    if raw[1..].iter().all(|&b| b == 0) && (raw[0] == b'.' || raw[0] == b'_' || raw[0].is_ascii_uppercase()) {
+        return None;
+    }
+
+    // 0.fraction * 16^(exponent - 64), with a 56-bit fraction
+    let sign = if raw[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = (raw[0] & 0x7f) as i32 - 64;
+    let fraction = raw[1..].iter().fold(0u64, |f, &b| (f << 8) | b as u64);
+    Some(sign * fraction as f64 * 2f64.powi(4 * exponent - 56))
+}
+
+/// Split a format like `DATETIME20.` or `COMMA12.2` into its name, width and decimals
+pub(crate) fn parse_format(format: &str) -> (String, u32, u32) {
+    let format = format.trim();
+    let (body, decimals) = match format.rsplit_once('.') {
+        Some((body, decimals)) => (body, decimals.parse().unwrap_or(0)),
+        None => (format, 0),
+    };
+    let name_end = body.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let width = body[name_end..].parse().unwrap_or(0);
+    (body[..name_end].to_string(), width, decimals)
+}
+
+struct XportColumn {
+    metadata: SasColumnMetadata,
+    numeric: bool,
+    // Offset of the value in the row
+    position: usize,
+    dtype: DataType,
+    // Keep the special missing values as tagged NaNs, see `labels::tagged_missing`
+    tagged_missing: bool,
+}
+
+impl XportColumn {
+    fn decode(&self, rows: &[u8], row_length: usize) -> Column {
+        let name = PlSmallStr::from(self.metadata.name.as_str());
+        let length = self.metadata.length as usize;
+        let values = rows.chunks_exact(row_length).map(|row| &row[self.position..self.position + length]);
+        if !self.numeric {
+            return StringChunked::from_iter_values(name, values.map(text)).into_column();
+        }
+
+        let raw = values.clone();
+        let values = values.map(ibm_to_f64);
+        match self.dtype {
+            DataType::Date => Int32Chunked::from_iter_options(
+                name,
+                values.map(|v| v.map(|v| (v.floor() - EPOCH_OFFSET_DAYS) as i32)),
+            ).into_date().into_column(),
+            DataType::Datetime(..) => Int64Chunked::from_iter_options(
+                name,
+                values.map(|v| v.map(|v| ((v - EPOCH_OFFSET_SECONDS) * 1e6).round() as i64)),
+            ).into_datetime(TimeUnit::Microseconds, None).into_column(),
+            DataType::Time => Int64Chunked::from_iter_options(
+                name,
+                values.map(|v| v.map(|v| (v * 1e6).round() as i64 * 1000)),
+            ).into_time().into_column(),
+            _ if self.tagged_missing => Float64Chunked::from_iter_options(
+                name,
+                raw.map(|bytes| ibm_to_f64(bytes).or_else(|| match bytes[0] {
+                    tag @ (b'_' | b'A'..=b'Z') => Some(labels::tagged_missing(tag as char)),
+                    _ => None,
+                })),
+            ).into_column(),
+            _ => Float64Chunked::from_iter_options(name, values).into_column(),
+        }
+    }
+}
+
+/// Reader of the first member of a SAS transport (XPORT) library, V5 or V8/V9.
+///
+/// The observations follow the headers in 80-byte records, without any index: they are
+/// decoded in order, `chunk_size` rows at a time. Numeric columns with a date, datetime or
+/// time format are converted as in a `.sas7bdat` file.
+pub(crate) struct XportReader {
+    source: BufReader<Box<dyn ReadSeek>>,
+    format: SasFileFormat,
+    metadata: SasMetadata,
+    columns: Vec<XportColumn>,
+    schema: Schema,
+    row_length: usize,
+    chunk_size: usize,
+    // Position of the first observation record, and its index from the start of the file
+    data_start: u64,
+    data_record: u64,
+    // Observation bytes read but not decoded yet, starting at the row `rows_read`
+    pending: Vec<u8>,
+    rows_read: u64,
+    end_of_data: bool,
+}
+
+impl XportReader {
+    /// Parse the headers of the first member. `temporal_type` classifies the format names.
+    pub(crate) fn open(
+        source: Box<dyn ReadSeek>,
+        options: &SasReadOptions,
+        temporal_type: impl Fn(&str) -> SasTemporalType,
+    ) -> PolarsResult<Self> {
+        let mut source = BufReader::new(source);
+        let start = source.stream_position()?;
+        let expect_header = |record: &Record, names: [&str; 2]| -> PolarsResult<HeaderRecord> {
+            parse_header(record).filter(|header| names.contains(&header.name.as_str())).ok_or_else(|| {
+                PolarsError::ComputeError(format!("Invalid XPORT file: expected the {} header record", names[0]).into())
+            })
+        };
+
+        let library = read_record(&mut source)?;
+        let format = match parse_header(&library).map(|header| header.name) {
+            Some(name) if name == "LIBRARY" => SasFileFormat::XportV5,
+            Some(name) if name == "LIBV8" => SasFileFormat::XportV8,
+            _ => return Err(PolarsError::ComputeError("Not a SAS XPORT file".into())),
+        };
+        let v8 = format == SasFileFormat::XportV8;
+        let library = read_record(&mut source)?;
+        let (sas_release, os_name) = (text(&library[24..32]), text(&library[32..40]));
+        read_record(&mut source)?; // Modification date
+
+        let member = expect_header(&read_record(&mut source)?, ["MEMBER", "MEMBV8"])?;
+        let namestr_length = match member.numbers[5] {
+            0 => 140,
+            length => length,
+        };
+        expect_header(&read_record(&mut source)?, ["DSCRPTR", "DSCPTV8"])?;
+        let member = read_record(&mut source)?;
+        let dataset_name = text(if v8 { &member[8..40] } else { &member[8..16] });
+        let label = text(&read_record(&mut source)?[32..72]);
+
+        let column_count = expect_header(&read_record(&mut source)?, ["NAMESTR", "NAMSTV8"])?.numbers[1];
+        let namestrs = read_bytes(&mut source, (column_count * namestr_length).next_multiple_of(RECORD_LENGTH))?;
+
+        let mut columns = Vec::with_capacity(column_count);
+        let mut numbers = Vec::with_capacity(column_count);
+        for namestr in namestrs.chunks_exact(namestr_length).take(column_count) {
+            let long_name = (v8 && namestr_length >= 120).then(|| text(&namestr[88..120])).unwrap_or_default();
+            let metadata = SasColumnMetadata {
+                name: if long_name.is_empty() { text(&namestr[8..16]) } else { long_name },
+                label: text(&namestr[16..56]),
+                format: text(&namestr[56..64]),
+                format_width: be16(&namestr[64..66]) as u32,
+                format_decimals: be16(&namestr[66..68]) as u32,
+                length: be16(&namestr[4..6]) as u32,
+            };
+            numbers.push(be16(&namestr[6..8]));
+            columns.push(XportColumn {
+                metadata,
+                numeric: be16(&namestr[0..2]) == 1,
+                position: u32::from_be_bytes([namestr[84], namestr[85], namestr[86], namestr[87]]) as usize,
+                dtype: DataType::Null,
+                tagged_missing: false,
+            });
+        }
+
+        // V8/V9: labels longer than 40 characters, and formats (V9)
+        let mut header = read_record(&mut source)?;
+        if let Some(labels) = parse_header(&header).filter(|header| header.name == "LABELV8" || header.name == "LABELV9") {
+            let v9 = labels.name == "LABELV9";
+            let labels_start = source.stream_position()?;
+            for _ in 0..labels.numbers[0] {
+                let lengths = read_bytes(&mut source, if v9 { 10 } else { 6 })?;
+                let lengths: Vec<usize> = lengths.chunks_exact(2).map(be16).collect();
+                let (number, name_length) = (lengths[0], lengths[1]);
+                let (format_length, informat_length, label_length) = match v9 {
+                    true => (lengths[2], lengths[3], lengths[4]),
+                    false => (0, 0, lengths[2]),
+                };
+                read_bytes(&mut source, name_length)?;
+                let label = text(&read_bytes(&mut source, label_length)?);
+                let format = text(&read_bytes(&mut source, format_length)?);
+                read_bytes(&mut source, informat_length)?;
+
+                let Some(index) = numbers.iter().position(|&n| n == number) else {
+                    continue;
+                };
+                let metadata = &mut columns[index].metadata;
+                metadata.label = label;
+                if !format.is_empty() {
+                    (metadata.format, metadata.format_width, metadata.format_decimals) = parse_format(&format);
+                }
+            }
+            let length = (source.stream_position()? - labels_start) as usize;
+            read_bytes(&mut source, length.next_multiple_of(RECORD_LENGTH) - length)?;
+            header = read_record(&mut source)?;
+        }
+        expect_header(&header, ["OBS", "OBSV8"])?;
+        let data_start = source.stream_position()?;
+
+        let tagged_missing_formats = labels::tagged_missing_formats(options);
+        let mut schema = Schema::with_capacity(columns.len());
+        for column in &mut columns {
+            column.dtype = match column.numeric {
+                false => DataType::String,
+                true => match temporal_type(&column.metadata.format) {
+                    SasTemporalType::Date => DataType::Date,
+                    SasTemporalType::Datetime => DataType::Datetime(TimeUnit::Microseconds, None),
+                    SasTemporalType::Time => DataType::Time,
+                    SasTemporalType::Number => DataType::Float64,
+                },
+            };
+            column.tagged_missing = column.dtype == DataType::Float64
+                && tagged_missing_formats.contains(&column.metadata.format.to_ascii_uppercase());
+            let name = PlSmallStr::from(column.metadata.name.as_str());
+            if schema.insert(name, column.dtype.clone()).is_some() {
+                return Err(PolarsError::Duplicate(
+                    format!("Duplicate column '{}' in the XPORT file", column.metadata.name).into(),
+                ));
+            }
+        }
+        let row_length = columns.iter()
+            .map(|column| column.position + column.metadata.length as usize)
+            .max()
+            .unwrap_or(0);
+
+        Ok(XportReader {
+            source,
+            format,
+            metadata: SasMetadata {
+                dataset_name,
+                label,
+                encoding: String::new(),
+                sas_release,
+                os_name,
+                row_count: None,
+                columns: columns.iter().map(|column| column.metadata.clone()).collect(),
+            },
+            columns,
+            schema,
+            row_length,
+            chunk_size: options.chunk_size.filter(|&size| size > 0).unwrap_or(65536) as usize,
+            data_start,
+            data_record: (data_start - start) / RECORD_LENGTH as u64,
+            pending: Vec::new(),
+            rows_read: 0,
+            end_of_data: false,
+        })
+    }
+
+    pub(crate) fn format(&self) -> SasFileFormat {
+        self.format
+    }
+
+    pub(crate) fn metadata(&self) -> &SasMetadata {
+        &self.metadata
+    }
+
+    pub(crate) fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub(crate) fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Arrow name of the type of a column, as reported for `.sas7bdat` files
+    pub(crate) fn type_name(&self, index: usize) -> Option<&'static str> {
+        let name = match self.columns.get(index)?.dtype {
+            DataType::String => "string",
+            DataType::Date => "date32[day]",
+            DataType::Datetime(..) => "timestamp[us]",
+            DataType::Time => "time64[us]",
+            _ => "double",
+        };
+        Some(name)
+    }
+
+    // Read the observation records until `pending` holds `rows` rows or the data ends
+    fn fill(&mut self, rows: usize) -> PolarsResult<()> {
+        let needed = rows * self.row_length;
+        while !self.end_of_data && self.pending.len() < needed {
+            let mut record = [0u8; RECORD_LENGTH];
+            match self.source.read_exact(&mut record) {
+                // The next member of the library starts after the data
+                Ok(()) if parse_header(&record).is_some_and(|header| header.name == "MEMBER" || header.name == "MEMBV8") => {}
+                Ok(()) => {
+                    self.pending.extend_from_slice(&record);
+                    continue;
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            // The last record is padded with blanks, which are not rows
+            self.end_of_data = true;
+            let mut length = self.pending.len() - self.pending.len() % self.row_length;
+            let last_record = self.pending.len().saturating_sub(RECORD_LENGTH);
+            while length >= self.row_length
+                && length - self.row_length >= last_record
+                && self.pending[length - self.row_length..length].iter().all(|&b| b == b' ')
+            {
+                length -= self.row_length;
+            }
+            self.pending.truncate(length);
+        }
+        Ok(())
+    }
+
+    /// Decode the next batch: the rows, the index of the first one and the range of the
+    /// 80-byte records holding them, None at the end of the data
+    pub(crate) fn next_batch(&mut self) -> PolarsResult<Option<(DataFrame, u64, Range<u64>)>> {
+        if self.row_length == 0 {
+            return Ok(None);
+        }
+        self.fill(self.chunk_size)?;
+        let rows = (self.pending.len() / self.row_length).min(self.chunk_size);
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let batch: Vec<u8> = self.pending.drain(..rows * self.row_length).collect();
+        let columns = self.columns.iter()
+            .map(|column| column.decode(&batch, self.row_length))
+            .collect();
+        let df = DataFrame::new(columns)?;
+
+        let first_row = self.rows_read;
+        let start = first_row * self.row_length as u64;
+        let end = start + batch.len() as u64;
+        let records = self.data_record + start / RECORD_LENGTH as u64
+            ..self.data_record + (end - 1) / RECORD_LENGTH as u64 + 1;
+        self.rows_read += rows as u64;
+        Ok(Some((df, first_row, records)))
+    }
+
+    /// Read the observations from the first one again
+    pub(crate) fn reset(&mut self) -> PolarsResult<()> {
+        self.source.seek(SeekFrom::Start(self.data_start))?;
+        self.pending.clear();
+        self.rows_read = 0;
+        self.end_of_data = false;
+        Ok(())
+    }
+}
+
+/// Convert an IEEE double to an IBM System/370 double (big-endian), None when its
+/// magnitude exceeds the largest IBM double (about 7.2e75). Values smaller than the
+/// smallest one (about 5.4e-79) are written as 0.
+pub(crate) fn f64_to_ibm(value: f64) -> Option<[u8; 8]> {
+    let bits = value.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    // Subnormal doubles are far below the IBM range
+    if biased == 0 {
+        return Some([0; 8]);
+    }
+
+    // value = mantissa / 2^53 * 2^exponent = fraction / 2^56 * 16^hex_exponent
+    let mantissa = (bits & 0x000f_ffff_ffff_ffff) | 1 << 52;
+    let exponent = biased - 1022;
+    let hex_exponent = (exponent + 3).div_euclid(4);
+    let mut fraction = mantissa << (3 - (4 * hex_exponent - exponent));
+    let mut hex_exponent = hex_exponent + 64;
+    if hex_exponent > 127 {
+        return None;
+    }
+    while hex_exponent < 0 && fraction != 0 {
+        fraction >>= 4;
+        hex_exponent += 1;
+    }
+    if fraction == 0 {
+        return Some([0; 8]);
+    }
+
+    let mut ibm = fraction.to_be_bytes();
+    ibm[0] = hex_exponent as u8 | ((bits >> 56) as u8 & 0x80);
+    Some(ibm)
+}
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+// The current time as `ddMMMyy:hh:mm:ss`
+fn timestamp() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Civil date of a day count since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:02}{}{:02}:{:02}:{:02}:{:02}",
+        day,
+        MONTHS[month as usize - 1],
+        year.rem_euclid(100),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+fn header_record(name: &str, numbers: &str) -> Vec<u8> {
+    [
+        b"HEADER RECORD*******".as_slice(),
+        &field(name, 8),
+        b"HEADER RECORD!!!!!!!",
+        &field(numbers, 32),
+    ].concat()
+}
+
+// Limits of a transport file version, in bytes
+struct Limits {
+    name: usize,
+    label: usize,
+    string: usize,
+    version: &'static str,
+}
+
+impl Limits {
+    fn of(version: SasXportVersion) -> Self {
+        match version {
+            SasXportVersion::V5 => Limits { name: 8, label: 40, string: 200, version: "V5" },
+            SasXportVersion::V8 => Limits { name: 32, label: 256, string: 32767, version: "V8" },
+        }
+    }
+
+    // `text` if it fits in `limit` bytes, truncated or an error otherwise
+    fn check<'a>(&self, text: &'a str, limit: usize, what: &str, truncate: bool) -> PolarsResult<&'a str> {
+        if text.len() <= limit {
+            return Ok(text);
+        }
+        if truncate {
+            return Ok(truncated(text, limit));
+        }
+        let shown = truncated(text, 64);
+        Err(PolarsError::ComputeError(
+            format!(
+                "The {} '{}{}' is longer than {} bytes, the XPORT {} limit (enable truncation to shorten it)",
+                what, shown, if shown.len() < text.len() { "..." } else { "" }, limit, self.version
+            ).into(),
+        ))
+    }
+}
+
+// A column of the written dataset with its values encoded as in the rows
+struct XportWriteColumn {
+    name: String,
+    label: String,
+    format: (String, u32, u32),
+    numeric: bool,
+    length: usize,
+    values: Vec<u8>,
+}
+
+impl XportWriteColumn {
+    fn encode(column: &Column, limits: &Limits, options: &SasXportWriteOptions) -> PolarsResult<Self> {
+        let name = column.name().as_str();
+        let (numbers, default_format) = match sas_values(column)? {
+            SasValues::Numbers(numbers, default_format) => (numbers, default_format),
+            SasValues::Strings(strings) => return Self::encode_strings(name, &strings, limits, options),
+        };
+
+        let mut values = Vec::with_capacity(numbers.len() * 8);
+        for value in &numbers {
+            match value.filter(|v| !v.is_nan()) {
+                None => values.extend_from_slice(b".\0\0\0\0\0\0\0"),
+                Some(value) => values.extend_from_slice(&f64_to_ibm(value).ok_or_else(|| {
+                    PolarsError::ComputeError(
+                        format!("The value {} of the column '{}' is too large for an XPORT file", value, name).into(),
+                    )
+                })?),
+            }
+        }
+        Self::new(name, true, 8, values, default_format, limits, options)
+    }
+
+    fn encode_strings(
+        name: &str,
+        strings: &StringChunked,
+        limits: &Limits,
+        options: &SasXportWriteOptions,
+    ) -> PolarsResult<Self> {
+        let mut length = 1;
+        for value in strings.into_iter().flatten() {
+            let value = limits.check(value, limits.string, &format!("value of the column '{}'", name), options.truncate)?;
+            length = length.max(value.len());
+        }
+
+        let mut values = Vec::with_capacity(strings.len() * length);
+        for value in strings.into_iter() {
+            values.extend(field(truncated(value.unwrap_or(""), length), length));
+        }
+        Self::new(name, false, length, values, "", limits, options)
+    }
+
+    fn new(
+        name: &str,
+        numeric: bool,
+        length: usize,
+        values: Vec<u8>,
+        default_format: &str,
+        limits: &Limits,
+        options: &SasXportWriteOptions,
+    ) -> PolarsResult<Self> {
+        let lookup = |pairs: &[(String, String)]| {
+            pairs.iter().rev().find(|(column, _)| column == name).map(|(_, value)| value.clone())
+        };
+        let label = lookup(&options.labels).unwrap_or_default();
+        let format = lookup(&options.formats).unwrap_or_else(|| default_format.to_string());
+        let format = parse_format(&format);
+        if format.0.len() > 8 {
+            return Err(PolarsError::ComputeError(
+                format!("The format name '{}' of the column '{}' is longer than 8 characters", format.0, name).into(),
+            ));
+        }
+
+        Ok(XportWriteColumn {
+            name: limits.check(name, limits.name, "column name", options.truncate)?.to_string(),
+            label: limits.check(&label, limits.label, "label", options.truncate)?.to_string(),
+            format,
+            numeric,
+            length,
+            values,
+        })
+    }
+
+    fn namestr(&self, number: usize, position: usize, v8: bool) -> Vec<u8> {
+        let mut namestr = Vec::with_capacity(140);
+        namestr.extend_from_slice(&(if self.numeric { 1u16 } else { 2 }).to_be_bytes());
+        namestr.extend_from_slice(&[0, 0]);
+        namestr.extend_from_slice(&(self.length as u16).to_be_bytes());
+        namestr.extend_from_slice(&(number as u16).to_be_bytes());
+        namestr.extend(field(truncated(&self.name, 8), 8));
+        namestr.extend(field(truncated(&self.label, 40), 40));
+        namestr.extend(field(&self.format.0, 8));
+        namestr.extend_from_slice(&(self.format.1 as u16).to_be_bytes());
+        namestr.extend_from_slice(&(self.format.2 as u16).to_be_bytes());
+        // Justification, filler, informat name, width and decimals
+        namestr.extend_from_slice(&[0; 4]);
+        namestr.extend(field("", 8));
+        namestr.extend_from_slice(&[0; 4]);
+        namestr.extend_from_slice(&(position as u32).to_be_bytes());
+        if v8 {
+            namestr.extend(field(&self.name, 32));
+            namestr.extend_from_slice(&(self.label.len() as u16).to_be_bytes());
+        }
+        namestr.resize(140, 0);
+        namestr
+    }
+}
+
+// Pad the bytes written since the start of a section to a whole record
+fn pad_record(out: &mut impl Write, length: usize) -> PolarsResult<()> {
+    let padding = length.next_multiple_of(RECORD_LENGTH) - length;
+    out.write_all(&vec![b' '; padding])?;
+    Ok(())
+}
+
+fn write_library(df: &DataFrame, out: &mut impl Write, options: &SasXportWriteOptions) -> PolarsResult<()> {
+    let limits = Limits::of(options.version);
+    let v8 = options.version == SasXportVersion::V8;
+    let columns = df.get_columns().iter()
+        .map(|column| XportWriteColumn::encode(column, &limits, options))
+        .collect::<PolarsResult<Vec<_>>>()?;
+    let mut names = std::collections::HashSet::new();
+    for column in &columns {
+        if !names.insert(column.name.to_uppercase()) {
+            return Err(PolarsError::Duplicate(
+                format!("Duplicate column '{}' in the XPORT file", column.name).into(),
+            ));
+        }
+    }
+    let dataset_name = limits.check(&options.dataset_name, limits.name, "dataset name", options.truncate)?;
+    let dataset_label = limits.check(&options.dataset_label, 40, "dataset label", options.truncate)?;
+
+    let created = timestamp();
+    let zeros = "0".repeat(30);
+    let release = "SAS     SAS     SASLIB  9.4     ";
+    out.write_all(&header_record(if v8 { "LIBV8" } else { "LIBRARY" }, &zeros))?;
+    out.write_all(&[release.as_bytes(), &field("", 32), created.as_bytes()].concat())?;
+    out.write_all(&field(&created, RECORD_LENGTH))?;
+
+    out.write_all(&header_record(if v8 { "MEMBV8" } else { "MEMBER" }, "000000000000000001600000000140"))?;
+    out.write_all(&header_record(if v8 { "DSCPTV8" } else { "DSCRPTR" }, &zeros))?;
+    let member = match v8 {
+        true => [b"SAS     ".as_slice(), &field(dataset_name, 32), b"SASDATA 9.4     ", &field("", 8), created.as_bytes()].concat(),
+        false => [b"SAS     ".as_slice(), &field(dataset_name, 8), b"SASDATA 9.4     ", &field("", 32), created.as_bytes()].concat(),
+    };
+    out.write_all(&member)?;
+    out.write_all(&[created.as_bytes(), &field("", 16), &field(dataset_label, 40), &field("", 8)].concat())?;
+
+    let count = format!("00000{:05}{}", columns.len(), "0".repeat(20));
+    out.write_all(&header_record(if v8 { "NAMSTV8" } else { "NAMESTR" }, &count))?;
+    let mut position = 0;
+    for (i, column) in columns.iter().enumerate() {
+        out.write_all(&column.namestr(i + 1, position, v8))?;
+        position += column.length;
+    }
+    pad_record(out, columns.len() * 140)?;
+    let row_length = position;
+
+    // V8: the labels that do not fit in the namestr
+    let long_labels: Vec<_> = columns.iter().enumerate().filter(|(_, column)| v8 && column.label.len() > 40).collect();
+    if !long_labels.is_empty() {
+        out.write_all(&header_record("LABELV8", &long_labels.len().to_string()))?;
+        let mut length = 0;
+        for (i, column) in long_labels {
+            for number in [i + 1, column.name.len(), column.label.len()] {
+                out.write_all(&(number as u16).to_be_bytes())?;
+            }
+            out.write_all(column.name.as_bytes())?;
+            out.write_all(column.label.as_bytes())?;
+            length += 6 + column.name.len() + column.label.len();
+        }
+        pad_record(out, length)?;
+    }
+
+    out.write_all(&header_record(if v8 { "OBSV8" } else { "OBS" }, &zeros))?;
+    for row in 0..df.height() {
+        for column in &columns {
+            out.write_all(&column.values[row * column.length..(row + 1) * column.length])?;
+        }
+    }
+    pad_record(out, df.height() * row_length)
+}
+
+/// Write a DataFrame as a SAS transport (XPORT) file holding one dataset.
+///
+/// Numeric, boolean and decimal columns are written as IBM doubles, with NaN and nulls
+/// as the missing value `.`. Date, datetime and time columns are written as SAS values
+/// with the `DATE9.`, `DATETIME20.` and `TIME8.` formats, time-zone-aware datetimes in
+/// UTC. String and categorical columns are written with the length of their longest
+/// value, nulls as blanks. Names, labels and strings over the limits of the version fail
+/// the write unless truncation is enabled.
+///
+/// The last record is padded with blanks: trailing rows made of blanks only, as rows of
+/// empty strings, cannot be told apart from the padding and are not read back.
+pub fn write_xpt(df: &DataFrame, path: &str, options: SasXportWriteOptions) -> PolarsResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_library(df, &mut out, &options)?;
+    out.flush()?;
+    Ok(())
+}
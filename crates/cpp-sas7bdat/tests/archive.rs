@@ -0,0 +1,94 @@
+#![cfg(any(feature = "zip", feature = "tar"))]
+
+#[cfg(feature = "zip")]
+use std::io::Write;
+use std::path::Path;
+
+use cpp_sas7bdat::{SasBatchIterator, SasReadOptions, SasReader};
+
+mod common;
+use common::{collect, read_path, test_file};
+
+const MEMBERS: [&str; 2] = ["data_AHS2013/omov.sas7bdat", "data_pandas/airline.sas7bdat"];
+
+// Every member is listed and reads as the original file, by name and by in-archive path
+fn check_archive(archive: &Path) {
+    let archive_path = archive.to_str().unwrap();
+    let members = SasReader::list_archive_members(archive_path).unwrap();
+    let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+    assert_eq!(names, MEMBERS);
+
+    for member in &members {
+        let expected = read_path(&test_file(&member.name), 1000);
+        assert_eq!(member.size, std::fs::metadata(test_file(&member.name)).unwrap().len());
+        let metadata = SasReader::read_archive_member_metadata(archive_path, &member.name).unwrap();
+        let reader = SasReader::open(&test_file(&member.name), SasReadOptions::new()).unwrap();
+        assert_eq!(metadata, reader.metadata().unwrap());
+        assert_eq!(metadata.row_count, Some(expected.height() as u64));
+
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        let df = SasBatchIterator::open_archive_member(archive_path, &member.name, options)
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected), "{}", member.name);
+
+        let in_archive_path = archive.join(&member.name);
+        let df = SasBatchIterator::new(in_archive_path.to_str().unwrap(), Some(1000))
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected), "{}", member.name);
+    }
+
+    let missing = SasReader::open_archive_member(archive_path, "missing.sas7bdat", SasReadOptions::new());
+    assert!(missing.is_err());
+    assert!(SasReader::read_archive_member_metadata(archive_path, "missing.sas7bdat").is_err());
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn reads_zip_members() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("vendor.zip");
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    // One stored and one deflated member
+    for (member, method) in MEMBERS.iter().zip([zip::CompressionMethod::Stored, zip::CompressionMethod::Deflated]) {
+        let options = zip::write::SimpleFileOptions::default().compression_method(method);
+        writer.start_file(*member, options).unwrap();
+        writer.write_all(&std::fs::read(test_file(member)).unwrap()).unwrap();
+    }
+    writer.add_directory("empty/", zip::write::SimpleFileOptions::default()).unwrap();
+    writer.start_file("README.txt", zip::write::SimpleFileOptions::default()).unwrap();
+    writer.write_all(b"not a dataset").unwrap();
+    writer.finish().unwrap();
+
+    check_archive(&path);
+}
+
+#[cfg(feature = "tar")]
+#[test]
+fn reads_tar_members() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("vendor.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&path).unwrap());
+    for member in MEMBERS {
+        builder.append_path_with_name(test_file(member), member).unwrap();
+    }
+    builder.finish().unwrap();
+
+    check_archive(&path);
+}
+
+#[cfg(all(feature = "tar", feature = "gzip"))]
+#[test]
+fn reads_compressed_tar_members() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("vendor.tar.gz");
+    let encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for member in MEMBERS {
+        builder.append_path_with_name(test_file(member), member).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+
+    check_archive(&path);
+}
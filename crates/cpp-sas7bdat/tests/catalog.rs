@@ -0,0 +1,83 @@
+use cpp_sas7bdat::{SasCatalog, SasRange};
+
+mod common;
+use common::test_file;
+
+fn number(value: f64, label: &str) -> (SasRange, String) {
+    (SasRange::Numbers(Some(value), Some(value)), label.to_string())
+}
+
+#[test]
+fn catalog_formats_are_read() {
+    let catalog = SasCatalog::open(&test_file("data_gov/formats.sas7bcat")).unwrap();
+    assert_eq!(catalog.name, "FORMATS");
+    assert_eq!(catalog.formats.len(), 176);
+
+    assert_eq!(catalog.get("F_P190F").unwrap(), [
+        number(0.0, "Not imputed (original data)"),
+        number(4.0, "Imputed by using a donor value"),
+        number(5.0, "Data adjusted by analyst during review"),
+    ]);
+    assert_eq!(catalog.get("LEVELF").unwrap(), [
+        number(1.0, "Elementary"),
+        number(2.0, "Secondary"),
+        number(3.0, "Combined elementary and secondary"),
+    ]);
+    let strings = |value: &str| SasRange::Strings(Some(value.to_string()), Some(value.to_string()));
+    assert_eq!(catalog.get("$FRAMEF").unwrap(), [
+        (strings("AREA"), "School is part of area frame".to_string()),
+        (strings("LIST"), "School is part of list frame".to_string()),
+    ]);
+
+    // Names longer than 8 characters and names with a width
+    let states = catalog.get("pstansif2.").unwrap();
+    assert_eq!(states.len(), 51);
+    assert_eq!(states[0], number(1.0, "Alabama"));
+    assert_eq!(states[50], number(56.0, "Wyoming"));
+    assert_eq!(catalog.get("ULOCALE18F").unwrap()[0], number(11.0, "City, Large"));
+    assert_eq!(catalog.get("HIGR2018F").unwrap().len(), 17);
+    assert!(catalog.get("MISSINGF").is_none());
+}
+
+#[test]
+fn other_files_are_not_catalogs() {
+    assert!(SasCatalog::open(&test_file("data_AHS2013/homimp.sas7bdat")).is_err());
+    assert!(SasCatalog::from_bytes(b"not a catalog").is_err());
+
+    let mut bytes = std::fs::read(test_file("data_gov/formats.sas7bcat")).unwrap();
+    bytes.truncate(bytes.len() / 2);
+    assert!(SasCatalog::from_bytes(&bytes).is_err());
+}
+
+// Offset of the first byte of the `count` entries of `format`, stored after its name
+fn entries(bytes: &[u8], format: &[u8], length: usize, count: usize) -> Vec<usize> {
+    let name = bytes.windows(format.len()).position(|window| window == format).unwrap();
+    (0..count).map(|i| name + 118 + i * length).collect()
+}
+
+#[test]
+fn low_high_and_other_are_read_from_the_entry_flags() {
+    let mut bytes = std::fs::read(test_file("data_gov/formats.sas7bcat")).unwrap();
+    let levels = entries(&bytes, b"LEVELF  ", 54, 3);
+    let frames = entries(&bytes, b"$FRAMEF ", 38, 2);
+    // LOW-1, OTHER, 3-HIGH
+    bytes[levels[0]] |= 0x40;
+    bytes[levels[1]] |= 0x10;
+    bytes[levels[2]] |= 0x20;
+    // OTHER, LOW-LIST
+    bytes[frames[0]] |= 0x10;
+    bytes[frames[1]] |= 0x40;
+
+    let catalog = SasCatalog::from_bytes(&bytes).unwrap();
+    assert_eq!(catalog.get("LEVELF").unwrap(), [
+        (SasRange::Numbers(None, Some(1.0)), "Elementary".to_string()),
+        (SasRange::Other, "Secondary".to_string()),
+        (SasRange::Numbers(Some(3.0), None), "Combined elementary and secondary".to_string()),
+    ]);
+    assert_eq!(catalog.get("$FRAMEF").unwrap(), [
+        (SasRange::Other, "School is part of area frame".to_string()),
+        (SasRange::Strings(None, Some("LIST".to_string())), "School is part of list frame".to_string()),
+    ]);
+    // The other formats are unchanged
+    assert_eq!(catalog.get("F_P190F").unwrap()[0], number(0.0, "Not imputed (original data)"));
+}
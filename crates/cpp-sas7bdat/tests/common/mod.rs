@@ -0,0 +1,72 @@
+// Helpers shared by the integration tests, each test crate uses some of them
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use cpp_sas7bdat::{SasBatchIterator, SasReadOptions};
+use polars::prelude::*;
+
+pub fn test_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("vendor/test")
+}
+
+/// Path of a file of `vendor/test`, e.g. `data_pandas/airline.sas7bdat`
+pub fn test_file(name: &str) -> String {
+    test_dir().join(name).to_string_lossy().into_owned()
+}
+
+/// The test files read by the C++ test suite (the keys of `files.json`) that are present on
+/// disk, the large ones being downloaded by `retrieve.bash` only. Every one of them must be read.
+pub fn readable_test_files() -> Vec<String> {
+    let index = std::fs::read_to_string(test_dir().join("files.json")).unwrap();
+    let mut files: Vec<String> = index
+        .lines()
+        .filter_map(|line| line.strip_prefix("  \"")?.strip_suffix("\": {"))
+        .map(test_file)
+        .filter(|file| PathBuf::from(file).is_file())
+        .collect();
+    files.sort();
+    assert!(files.len() > 100, "{} readable test files", files.len());
+    files
+}
+
+/// Stack the batches in a single DataFrame
+pub fn collect(batches: impl IntoIterator<Item = PolarsResult<DataFrame>>) -> PolarsResult<DataFrame> {
+    let mut df: Option<DataFrame> = None;
+    for batch in batches {
+        let batch = batch?;
+        match df.as_mut() {
+            Some(df) => {
+                df.vstack_mut(&batch)?;
+            }
+            None => df = Some(batch),
+        }
+    }
+    Ok(df.unwrap_or_default())
+}
+
+/// Read a whole file opened with `options`
+pub fn read_all(path: &str, options: SasReadOptions) -> PolarsResult<DataFrame> {
+    collect(SasBatchIterator::open(path, options)?)
+}
+
+/// Read a whole file from its path in batches of `chunk_size` rows, the reference of the
+/// other sources
+pub fn read_path(path: &str, chunk_size: u32) -> DataFrame {
+    SasBatchIterator::new(path, Some(chunk_size))
+        .and_then(collect)
+        .unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Check that `read` gives the rows of the path reads of `files` in batches of `chunk_size`
+pub fn assert_reads_match_path_reads<S: AsRef<str>>(
+    files: &[S],
+    chunk_size: u32,
+    read: impl Fn(&str) -> PolarsResult<DataFrame>,
+) {
+    for path in files {
+        let path = path.as_ref();
+        let df = read(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        assert!(df.equals_missing(&read_path(path, chunk_size)), "{}: rows differ", path);
+    }
+}
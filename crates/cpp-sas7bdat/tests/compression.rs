@@ -0,0 +1,70 @@
+#![cfg(any(feature = "gzip", feature = "zstd", feature = "bzip2", feature = "xz"))]
+
+use std::io::Write;
+
+use cpp_sas7bdat::{SasBatchIterator, SasParallelBatchIterator, SasReadOptions};
+
+mod common;
+use common::{collect, read_path, test_file};
+
+const FILE: &str = "data_AHS2013/omov.sas7bdat";
+
+// Compress the test file with `compress` and compare its rows with the uncompressed file
+fn check_compressed(extension: &str, compress: impl FnOnce(&[u8]) -> Vec<u8>) {
+    let expected = read_path(&test_file(FILE), 1000);
+
+    let dir = tempfile::tempdir().unwrap();
+    // No compression extension: the codec is detected from the magic bytes
+    for name in [format!("omov.sas7bdat.{}", extension), "omov.dat".to_string()] {
+        let path = dir.path().join(name);
+        std::fs::write(&path, compress(&std::fs::read(test_file(FILE)).unwrap())).unwrap();
+
+        let df = SasBatchIterator::new(path.to_str().unwrap(), Some(1000))
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected));
+
+        // Read sequentially by a single reader
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        let df = SasParallelBatchIterator::new(path.to_str().unwrap(), options)
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected));
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn reads_gzip_files() {
+    check_compressed("gz", |data| {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    });
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn reads_zstd_files() {
+    check_compressed("zst", |data| zstd::encode_all(data, 0).unwrap());
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn reads_bzip2_files() {
+    check_compressed("bz2", |data| {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    });
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn reads_xz_files() {
+    check_compressed("xz", |data| {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    });
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use cpp_sas7bdat::{SasBatchIterator, SasDatasetReader, SasReadOptions, SasReader};
+use polars::prelude::*;
+
+mod common;
+use common::{collect, test_file};
+
+#[test]
+fn reads_files_in_turn_with_the_source_file() {
+    let files = [test_file("data_pandas/test1.sas7bdat"), test_file("data_pandas/test2.sas7bdat")];
+    let dataset = SasDatasetReader::new(&files, SasReadOptions::new().with_chunk_size(7))
+        .unwrap()
+        .with_source_file_column("source_file")
+        .unwrap();
+    let df = collect(dataset.batches().unwrap()).unwrap();
+    let schema = dataset.schema();
+    assert_eq!(df.get_column_names(), schema.iter_names().collect::<Vec<_>>());
+
+    let mut offset = 0;
+    for file in &files {
+        let expected = collect(SasBatchIterator::new(file, Some(1000)).unwrap()).unwrap();
+        let rows = df.slice(offset, expected.height());
+        for column in expected.get_columns() {
+            let unified = rows.column(column.name()).unwrap();
+            assert!(unified.equals_missing(&column.cast(unified.dtype()).unwrap()), "{}", column.name());
+        }
+        let source_files = rows.column("source_file").unwrap().str().unwrap();
+        assert!(source_files.into_iter().all(|source_file| source_file == Some(file.as_str())));
+        offset += expected.height() as i64;
+    }
+    assert_eq!(offset as usize, df.height());
+}
+
+#[test]
+fn unifies_schemas_by_name() {
+    let files = [test_file("data_pandas/airline.sas7bdat"), test_file("data_pandas/test1.sas7bdat")];
+    let dataset = SasDatasetReader::new(&files, SasReadOptions::new()).unwrap();
+    let df = collect(dataset.batches().unwrap()).unwrap();
+
+    let mut height = 0;
+    for (file, other) in [(&files[0], &files[1]), (&files[1], &files[0])] {
+        let expected = collect(SasBatchIterator::new(file, None).unwrap()).unwrap();
+        let other = SasReader::read_sas_schema(other).unwrap();
+        for name in expected.get_column_names() {
+            assert!(dataset.schema().contains(name));
+            // Rows of the file without the column are nulls
+            if !other.contains(name) {
+                let nulls = df.column(name).unwrap().null_count() - expected.column(name).unwrap().null_count();
+                assert_eq!(nulls, df.height() - expected.height(), "{}", name);
+            }
+        }
+        height += expected.height();
+    }
+    assert_eq!(df.height(), height);
+}
+
+// Number of rows of each source file
+fn rows_by_file(df: &DataFrame) -> HashMap<String, usize> {
+    let mut rows = HashMap::new();
+    for source_file in df.column("source_file").unwrap().str().unwrap() {
+        *rows.entry(source_file.unwrap().to_string()).or_default() += 1;
+    }
+    rows
+}
+
+#[test]
+fn parallel_reads_yield_every_row() {
+    let files: Vec<String> = ["test1", "test2", "test3", "test4"]
+        .iter()
+        .map(|name| test_file(&format!("data_pandas/{}.sas7bdat", name)))
+        .collect();
+    let dataset = SasDatasetReader::new(&files, SasReadOptions::new().with_chunk_size(3))
+        .unwrap()
+        .with_source_file_column("source_file")
+        .unwrap();
+    let sequential = collect(dataset.batches().unwrap()).unwrap();
+    let parallel = collect(dataset.with_parallel(true).batches().unwrap()).unwrap();
+
+    assert_eq!(parallel.height(), sequential.height());
+    assert_eq!(rows_by_file(&parallel), rows_by_file(&sequential));
+}
+
+#[test]
+fn missing_files_are_reported() {
+    let files = [test_file("data_pandas/test1.sas7bdat"), test_file("missing.sas7bdat")];
+    let error = SasDatasetReader::new(&files, SasReadOptions::new()).err().unwrap();
+    assert!(error.to_string().contains("missing.sas7bdat"));
+}
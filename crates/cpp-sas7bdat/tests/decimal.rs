@@ -0,0 +1,126 @@
+use cpp_sas7bdat::{SasErrorPolicy, SasReadOptions, SasReader, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+fn write(df: &DataFrame, options: SasWriteOptions) -> tempfile::NamedTempFile {
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options).unwrap();
+    writer.write_batch(df).unwrap();
+    writer.finish().unwrap();
+    file
+}
+
+fn decimal_options() -> SasReadOptions {
+    SasReadOptions::new().with_fixed_decimal(true)
+}
+
+// Unscaled values of a decimal column
+fn unscaled(df: &DataFrame, name: &str) -> Vec<Option<i128>> {
+    let column = df.column(name).unwrap();
+    (0..column.len())
+        .map(|i| match column.get(i).unwrap() {
+            AnyValue::Decimal(value, _) => Some(value),
+            AnyValue::Null => None,
+            value => panic!("{name}: {value:?} is not a decimal"),
+        })
+        .collect()
+}
+
+#[test]
+fn fixed_decimal_formats_are_read_as_decimals() {
+    // ACTUAL and PREDICT are formatted with DOLLAR12.2
+    let path = test_file("data_pandas/productsales.sas7bdat");
+    let mut reader = SasReader::with_options(&path, decimal_options()).unwrap();
+    let schema = reader.get_schema().unwrap().clone();
+    for name in ["ACTUAL", "PREDICT"] {
+        assert_eq!(schema.get(name), Some(&DataType::Decimal(Some(12), Some(2))));
+    }
+    // No width: read as numbers
+    assert_eq!(schema.get("YEAR"), Some(&DataType::Float64));
+
+    let df = read_all(&path, decimal_options()).unwrap();
+    assert_eq!(df.height(), 1440);
+    assert_eq!(&unscaled(&df, "ACTUAL")[..2], [Some(92_500), Some(99_900)]);
+    assert_eq!(&unscaled(&df, "PREDICT")[..2], [Some(85_000), Some(29_700)]);
+
+    // Without the option the columns stay Float64
+    let mut reader = SasReader::with_options(&path, SasReadOptions::new()).unwrap();
+    assert_eq!(reader.get_schema().unwrap().get("ACTUAL"), Some(&DataType::Float64));
+}
+
+#[test]
+fn values_are_rounded_to_nearest_ties_to_even() {
+    let df = df!(
+        // 0.125, 0.375 and 2.5 are exact doubles, 1.005 and 2.675 are stored just below
+        "CENTS" => [Some(0.125), Some(0.375), Some(-0.125), Some(1.005), Some(2.675), None],
+        "UNITS" => [Some(2.5), Some(3.5), Some(-2.5), Some(0.5), Some(1.5), None],
+    ).unwrap();
+    let options = SasWriteOptions::new().with_format("CENTS", "8.2").with_format("UNITS", "COMMA8.");
+    let file = write(&df, options);
+    let path = file.path().to_str().unwrap();
+
+    let mut reader = SasReader::open(path, decimal_options()).unwrap();
+    assert_eq!(reader.get_schema().unwrap().iter_values().cloned().collect::<Vec<_>>(), [
+        DataType::Decimal(Some(8), Some(2)),
+        DataType::Decimal(Some(8), Some(0)),
+    ]);
+
+    let df = read_all(path, decimal_options()).unwrap();
+    assert_eq!(unscaled(&df, "CENTS"), [Some(12), Some(38), Some(-12), Some(100), Some(267), None]);
+    assert_eq!(unscaled(&df, "UNITS"), [Some(2), Some(4), Some(-2), Some(0), Some(2), None]);
+}
+
+#[test]
+fn values_beyond_the_precision_fail() {
+    let df = df!("AMOUNT" => [1.5, 999.99, 1234.5]).unwrap();
+    let file = write(&df, SasWriteOptions::new().with_format("AMOUNT", "5.2"));
+    let path = file.path().to_str().unwrap();
+
+    let mut reader = SasReader::open(path, decimal_options()).unwrap();
+    assert_eq!(reader.get_schema().unwrap().get("AMOUNT"), Some(&DataType::Decimal(Some(5), Some(2))));
+    let error = reader.read_next_batch().unwrap_err().to_string();
+    assert!(error.contains("AMOUNT"), "{error}");
+    assert!(error.contains("does not fit"), "{error}");
+
+    // Read as numbers, every value is kept
+    let df = read_all(path, SasReadOptions::new()).unwrap();
+    assert_eq!(df.column("AMOUNT").unwrap().f64().unwrap().get(2), Some(1234.5));
+}
+
+#[test]
+fn values_beyond_the_precision_are_nulls_under_the_null_policy() {
+    let df = df!(
+        "ID" => [1.0, 2.0, 3.0, 4.0, 5.0],
+        "AMOUNT" => [1.5, 999.99, 1234.5, 2.0, -5000.0],
+    ).unwrap();
+    let file = write(&df, SasWriteOptions::new().with_format("AMOUNT", "5.2"));
+    let path = file.path().to_str().unwrap();
+    let options = decimal_options().with_chunk_size(2).with_error_policy(SasErrorPolicy::Null);
+
+    let mut reader = SasReader::open(path, options.clone()).unwrap();
+    let mut diagnostics = Vec::new();
+    for _ in 0..3 {
+        let batch = reader.read_next_batch().unwrap();
+        // The other cells of the rows are kept
+        assert_eq!(batch.column("ID").unwrap().len(), batch.column("AMOUNT").unwrap().len());
+        diagnostics.extend(reader.batch_diagnostics().unwrap());
+    }
+    let cells: Vec<(u64, u32, &str)> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.row, diagnostic.column_index, diagnostic.column_name.as_str()))
+        .collect();
+    assert_eq!(cells, [(2, 1, "AMOUNT"), (4, 1, "AMOUNT")]);
+    assert!(diagnostics[0].message.contains("1234.5"), "{}", diagnostics[0].message);
+
+    let df = read_all(path, options).unwrap();
+    assert_eq!(df.height(), 5);
+    assert_eq!(unscaled(&df, "AMOUNT"), [Some(150), Some(99_999), None, Some(200), None]);
+    assert_eq!(df.column("ID").unwrap().null_count(), 0);
+
+    // Aborted by default
+    let mut reader = SasReader::open(path, decimal_options().with_chunk_size(2)).unwrap();
+    assert!(reader.read_next_batch().is_ok());
+    assert!(reader.read_next_batch().is_err());
+}
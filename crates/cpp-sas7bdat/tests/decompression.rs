@@ -0,0 +1,13 @@
+use cpp_sas7bdat::SasReadOptions;
+
+mod common;
+use common::{read_all, test_file};
+
+#[test]
+fn compressed_rows_are_decompressed_to_their_end() {
+    // SASYZCR2 (RDC) rows, ending with the 9 bytes of Column98
+    let path = test_file("data_pandas/test8.sas7bdat");
+    let df = read_all(&path, SasReadOptions::new()).unwrap();
+    let values = df.column("Column98").unwrap().str().unwrap().clone();
+    assert_eq!([values.get(0), values.get(1), values.get(9)], [Some("apple"), Some("dog"), Some("")]);
+}
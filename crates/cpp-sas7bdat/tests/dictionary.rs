@@ -0,0 +1,141 @@
+use cpp_sas7bdat::{write_xpt, SasReadOptions, SasReader, SasWriteOptions, SasWriter, SasXportWriteOptions};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+// 1440 sales of 5 products (PRODUCT) in 3 countries (COUNTRY) and 2 regions (REGION)
+fn sales() -> String {
+    test_file("data_pandas/productsales.sas7bdat")
+}
+
+const CITIES: [&str; 10] = ["PARIS", "LYON", "PARIS", "NICE", "LYON", "PARIS", "LILLE", "NICE", "PARIS", "BREST"];
+
+// CITY repeats a few values, some only after the first batch, CODE never repeats
+fn sample() -> tempfile::NamedTempFile {
+    let codes: Vec<String> = (0..CITIES.len()).map(|i| format!("C{i:02}")).collect();
+    let df = df!("CITY" => CITIES, "CODE" => codes).unwrap();
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), SasWriteOptions::new()).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+    file
+}
+
+fn is_categorical(df: &DataFrame, name: &str) -> bool {
+    matches!(df.column(name).unwrap().dtype(), DataType::Categorical(..))
+}
+
+fn strings(df: &DataFrame, name: &str) -> Vec<String> {
+    let column = df.column(name).unwrap().cast(&DataType::String).unwrap();
+    column.str().unwrap().into_no_null_iter().map(str::to_string).collect()
+}
+
+#[test]
+fn dictionary_columns_are_concatenated_across_batches() {
+    let path = sales();
+    let options = SasReadOptions::new().with_chunk_size(500).with_dictionary_column("COUNTRY");
+
+    let mut reader = SasReader::with_options(&path, options.clone()).unwrap();
+    let first = reader.read_next_batch().unwrap();
+    let second = reader.read_next_batch().unwrap();
+    assert!(is_categorical(&first, "COUNTRY") && is_categorical(&second, "COUNTRY"));
+    assert!(!is_categorical(&first, "REGION"));
+    // The country of the first row has the same physical value in both batches
+    let row = strings(&second, "COUNTRY").iter().position(|country| *country == strings(&first, "COUNTRY")[0]);
+    let physical = |df: &DataFrame, row| df.column("COUNTRY").unwrap().categorical().unwrap().physical().get(row);
+    assert_eq!(physical(&first, 0), physical(&second, row.unwrap()));
+
+    // Three batches, with the values of a plain read
+    let df = read_all(&path, options).unwrap();
+    assert!(is_categorical(&df, "COUNTRY"));
+    assert_eq!(strings(&df, "COUNTRY"), strings(&read_all(&path, SasReadOptions::new()).unwrap(), "COUNTRY"));
+}
+
+#[test]
+fn dictionary_columns_of_two_readers_can_be_combined() {
+    let path = sales();
+    let options = SasReadOptions::new().with_chunk_size(500).with_dictionary_column("COUNTRY");
+
+    // The first reader is dropped before the second is opened: the string cache is held meanwhile
+    let _cache = polars_core::StringCacheHolder::hold();
+    let first = read_all(&path, options.clone()).unwrap();
+    let second = read_all(&path, options).unwrap();
+    let mut df = first.clone();
+    df.vstack_mut(&second).unwrap();
+    assert!(is_categorical(&df, "COUNTRY"));
+    assert_eq!(strings(&df, "COUNTRY"), [strings(&first, "COUNTRY"), strings(&second, "COUNTRY")].concat());
+}
+
+#[test]
+fn detection_falls_back_to_strings_above_the_cardinality() {
+    let path = sales();
+    let options = SasReadOptions::new().with_dictionary_detection(3);
+
+    let mut reader = SasReader::with_options(&path, options.clone()).unwrap();
+    let schema = reader.get_schema().unwrap();
+    assert!(matches!(schema.get("COUNTRY"), Some(DataType::Categorical(..))));
+    assert!(matches!(schema.get("REGION"), Some(DataType::Categorical(..))));
+    assert_eq!(schema.get("PRODUCT"), Some(&DataType::String));
+    // The single batch, decoded to detect the columns, is still read
+    assert_eq!(reader.read_next_batch().unwrap().height(), 1440);
+    assert!(reader.read_next_batch().is_err());
+
+    let df = read_all(&path, options).unwrap();
+    let plain = read_all(&path, SasReadOptions::new()).unwrap();
+    assert!(is_categorical(&df, "COUNTRY"));
+    assert_eq!(strings(&df, "COUNTRY"), strings(&plain, "COUNTRY"));
+    assert_eq!(strings(&df, "PRODUCT"), strings(&plain, "PRODUCT"));
+
+    // At the cardinality of the products, they are dictionaries too
+    let mut reader = SasReader::with_options(&path, SasReadOptions::new().with_dictionary_detection(5)).unwrap();
+    assert!(matches!(reader.get_schema().unwrap().get("PRODUCT"), Some(DataType::Categorical(..))));
+}
+
+#[test]
+fn detection_reads_transport_files() {
+    let df = read_all(&sales(), SasReadOptions::new()).unwrap();
+    let xpt = tempfile::Builder::new().suffix(".xpt").tempfile().unwrap();
+    let xpt_path = xpt.path().to_str().unwrap();
+    write_xpt(&df, xpt_path, SasXportWriteOptions::default()).unwrap();
+
+    let read = read_all(xpt_path, SasReadOptions::new().with_dictionary_detection(3)).unwrap();
+    assert!(is_categorical(&read, "COUNTRY") && !is_categorical(&read, "PRODUCT"));
+    assert_eq!(strings(&read, "COUNTRY"), strings(&df, "COUNTRY"));
+}
+
+#[test]
+fn categories_first_seen_in_later_batches_are_added() {
+    let file = sample();
+    let path = file.path().to_str().unwrap();
+    let options = SasReadOptions::new().with_chunk_size(3).with_dictionary_column("CITY");
+
+    let mut reader = SasReader::open(path, options.clone()).unwrap();
+    let first = reader.read_next_batch().unwrap();
+    let second = reader.read_next_batch().unwrap();
+    assert!(is_categorical(&first, "CITY") && is_categorical(&second, "CITY"));
+    assert!(!is_categorical(&first, "CODE"));
+    // PARIS has the same physical value in both batches
+    let physical = |df: &DataFrame, row| df.column("CITY").unwrap().categorical().unwrap().physical().get(row);
+    assert_eq!(physical(&first, 0), physical(&second, 2));
+
+    // Four batches, the categories of the last ones are not in the first
+    let df = read_all(path, options).unwrap();
+    assert!(is_categorical(&df, "CITY"));
+    assert_eq!(strings(&df, "CITY"), CITIES);
+}
+
+#[test]
+fn detection_keeps_the_first_batch() {
+    let file = sample();
+    let path = file.path().to_str().unwrap();
+    let options = SasReadOptions::new().with_chunk_size(4).with_dictionary_detection(3);
+
+    // The first batch decoded for the schema is the first batch read
+    let mut reader = SasReader::open(path, options.clone()).unwrap();
+    reader.get_schema().unwrap();
+    assert!(reader.set_page_range(0, 1).is_err());
+    let first = reader.read_next_batch().unwrap();
+    assert!(is_categorical(&first, "CITY") && !is_categorical(&first, "CODE"));
+    assert_eq!(strings(&first, "CITY"), CITIES[..4]);
+}
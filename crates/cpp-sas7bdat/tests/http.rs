@@ -0,0 +1,121 @@
+#![cfg(feature = "http")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use cpp_sas7bdat::{SasBatchIterator, SasHttpOptions, SasReadOptions, SasReader};
+use polars::prelude::*;
+
+mod common;
+use common::{collect, read_path, test_dir, test_file};
+
+// Serve one request with Range support, counting the bytes of the response bodies
+fn serve(mut stream: TcpStream, served: &AtomicU64) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut range = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("range") {
+                range = value.trim().strip_prefix("bytes=").map(str::to_string);
+            }
+        }
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let Ok(data) = std::fs::read(test_dir().join(path.trim_start_matches('/'))) else {
+        return stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    };
+    let Some((start, end)) = range.as_deref().and_then(|range| range.split_once('-')) else {
+        return stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    };
+    let start: usize = start.parse().unwrap();
+    let end = end.parse::<usize>().unwrap().min(data.len() - 1);
+    let body = &data[start..=end];
+    served.fetch_add(body.len() as u64, Ordering::SeqCst);
+    write!(
+        stream,
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        start, end, data.len(), body.len()
+    )?;
+    stream.write_all(body)
+}
+
+// Start a server for the vendored test files, returns its URL and the served byte counter
+fn start_server() -> (String, Arc<AtomicU64>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let served = Arc::new(AtomicU64::new(0));
+    let counter = Arc::clone(&served);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = serve(stream, &counter);
+        }
+    });
+    (url, served)
+}
+
+#[test]
+fn from_url_matches_path_reads() {
+    let (url, _) = start_server();
+    for file in ["data_AHS2013/omov.sas7bdat", "data_pandas/airline.sas7bdat", "data_pandas/test1.sas7bdat"] {
+        let expected = read_path(&test_file(file), 1000);
+
+        // Small blocks so that the pages span several blocks and requests
+        let http_options = SasHttpOptions::new().with_block_size(4096).with_cache_blocks(8).with_read_ahead(3);
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        let df = SasBatchIterator::from_url(&format!("{}/{}", url, file), http_options, options)
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected), "{}", file);
+
+        let df = SasBatchIterator::new(&format!("{}/{}", url, file), Some(1000))
+            .and_then(collect)
+            .unwrap();
+        assert!(df.equals_missing(&expected), "{}", file);
+    }
+}
+
+#[test]
+fn schema_reads_fetch_the_metadata_only() {
+    let (url, served) = start_server();
+    let file = "data_AHS2013/homimp.sas7bdat";
+    let size = std::fs::metadata(test_dir().join(file)).unwrap().len();
+
+    let http_options = SasHttpOptions::new().with_block_size(16 * 1024);
+    let mut reader = SasReader::from_url(&format!("{}/{}", url, file), http_options, SasReadOptions::new()).unwrap();
+    let schema = reader.get_schema().unwrap().clone();
+    let expected = SasReader::read_sas_schema(test_dir().join(file).to_str().unwrap()).unwrap();
+
+    assert_eq!(schema, expected);
+    assert!(served.load(Ordering::SeqCst) < size / 4);
+}
+
+#[test]
+fn zero_block_sizes_are_clamped() {
+    let (url, _) = start_server();
+    let file = "data_pandas/test1.sas7bdat";
+    // Blocks of one byte, fetched 64 KiB at a time
+    let http_options = SasHttpOptions::new().with_cache_blocks(1 << 16).with_read_ahead(1 << 16);
+    let http_options = SasHttpOptions { block_size: 0, ..http_options };
+    let df = SasBatchIterator::from_url(&format!("{}/{}", url, file), http_options, SasReadOptions::new())
+        .and_then(collect)
+        .unwrap();
+    assert!(df.equals_missing(&read_path(&test_file(file), 1000)));
+}
+
+#[test]
+fn missing_urls_fail() {
+    let (url, _) = start_server();
+    let result = SasReader::from_url(&format!("{}/missing.sas7bdat", url), SasHttpOptions::new(), SasReadOptions::new());
+    assert!(result.is_err());
+}
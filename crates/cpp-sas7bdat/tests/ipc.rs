@@ -0,0 +1,131 @@
+#![cfg(feature = "ipc")]
+
+use std::fs::File;
+
+use cpp_sas7bdat::{
+    sas_to_ipc, SasIpcCompression, SasIpcFormat, SasIpcOptions, SasReadOptions, SasWriteOptions, SasWriter,
+    SasXportWriteOptions,
+};
+use polars::prelude::*;
+use polars_arrow::array::Array;
+use polars_arrow::datatypes::ArrowDataType;
+use polars_arrow::io::ipc::read::{read_file_metadata, read_stream_metadata, FileReader, StreamReader, StreamState};
+
+fn sample() -> tempfile::NamedTempFile {
+    let df = df!(
+        "ID" => [1.0, 2.0, 3.0, 4.0, 5.0],
+        "AMOUNT" => [Some(1234.5), None, Some(-0.25), Some(0.0), Some(99.0)],
+        "NAME" => ["one", "two", "three", "four", "five"],
+    ).unwrap();
+    let options = SasWriteOptions::new()
+        .with_dataset_name("SAMPLE")
+        .with_label("AMOUNT", "Amount paid")
+        .with_format("AMOUNT", "DOLLAR10.2");
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+    file
+}
+
+fn convert(input: &tempfile::NamedTempFile, options: SasIpcOptions) -> tempfile::NamedTempFile {
+    let output = tempfile::Builder::new().suffix(".arrow").tempfile().unwrap();
+    sas_to_ipc(input.path().to_str().unwrap(), output.path().to_str().unwrap(), options).unwrap();
+    output
+}
+
+#[test]
+fn batches_are_written_as_record_batches() {
+    let input = sample();
+    for compression in [SasIpcCompression::None, SasIpcCompression::Lz4, SasIpcCompression::Zstd] {
+        let options = SasIpcOptions::new()
+            .with_read_options(SasReadOptions::new().with_chunk_size(2))
+            .with_compression(compression);
+        let output = convert(&input, options);
+
+        let mut file = File::open(output.path()).unwrap();
+        let metadata = read_file_metadata(&mut file).unwrap();
+        assert_eq!(metadata.blocks.len(), 3);
+        let batches = FileReader::new(file, metadata, None, None);
+        let heights: Vec<usize> = batches.map(|batch| batch.unwrap().len()).collect();
+        assert_eq!(heights, [2, 2, 1]);
+    }
+}
+
+#[test]
+fn sas_metadata_is_kept() {
+    let input = sample();
+    let output = convert(&input, SasIpcOptions::new());
+
+    let metadata = read_file_metadata(&mut File::open(output.path()).unwrap()).unwrap();
+    let schema_metadata = metadata.custom_schema_metadata.unwrap();
+    assert_eq!(schema_metadata.get("sas.dataset_name").map(|value| value.as_str()), Some("SAMPLE"));
+    assert_eq!(schema_metadata.get("sas.row_count").map(|value| value.as_str()), Some("5"));
+
+    let amount = metadata.schema.get("AMOUNT").unwrap().metadata.clone().unwrap();
+    assert_eq!(amount.get("label").map(|value| value.as_str()), Some("Amount paid"));
+    assert_eq!(amount.get("format").map(|value| value.as_str()), Some("DOLLAR10.2"));
+    assert!(metadata.schema.get("ID").unwrap().metadata.is_none());
+
+    // The labels and formats are written back to a transport file
+    let options = SasXportWriteOptions::new().with_field_metadata(&metadata.schema);
+    assert_eq!(options.labels, [("AMOUNT".to_string(), "Amount paid".to_string())]);
+    assert_eq!(options.formats, [("AMOUNT".to_string(), "DOLLAR10.2".to_string())]);
+}
+
+#[test]
+fn stream_format() {
+    let input = sample();
+    let options = SasIpcOptions::new().with_format(SasIpcFormat::Stream).with_compat_level(CompatLevel::oldest());
+    let output = convert(&input, options);
+
+    let metadata = read_stream_metadata(&mut File::open(output.path()).unwrap()).unwrap();
+    let amount = metadata.schema.get("AMOUNT").unwrap().metadata.clone().unwrap();
+    assert_eq!(amount.get("label").map(|value| value.as_str()), Some("Amount paid"));
+    assert!(read_file_metadata(&mut File::open(output.path()).unwrap()).is_err());
+}
+
+// The values of the NAME column of the record batches as strings
+fn names(arrays: impl Iterator<Item = Box<dyn Array>>) -> Vec<String> {
+    let mut names = Vec::new();
+    for array in arrays {
+        let series = Series::from_arrow("NAME".into(), array).unwrap().cast(&DataType::String).unwrap();
+        names.extend(series.str().unwrap().into_no_null_iter().map(str::to_string));
+    }
+    names
+}
+
+#[test]
+fn categorical_columns_span_the_record_batches() {
+    let input = sample();
+    // Each batch adds categories to the dictionary of NAME
+    let read_options = SasReadOptions::new().with_chunk_size(2).with_dictionary_column("NAME");
+    let expected = ["one", "two", "three", "four", "five"];
+
+    // A file holds one dictionary per field: the categories are written as strings
+    let output = convert(&input, SasIpcOptions::new().with_read_options(read_options.clone()));
+    let mut file = File::open(output.path()).unwrap();
+    let metadata = read_file_metadata(&mut file).unwrap();
+    assert!(!matches!(metadata.schema.get("NAME").unwrap().dtype, ArrowDataType::Dictionary(..)));
+    let batches = FileReader::new(file, metadata, None, None);
+    assert_eq!(names(batches.map(|batch| batch.unwrap().columns()[2].clone())), expected);
+
+    // A stream replaces the dictionary
+    let options = SasIpcOptions::new().with_read_options(read_options).with_format(SasIpcFormat::Stream);
+    let output = convert(&input, options);
+    let mut file = File::open(output.path()).unwrap();
+    let metadata = read_stream_metadata(&mut file).unwrap();
+    assert!(matches!(metadata.schema.get("NAME").unwrap().dtype, ArrowDataType::Dictionary(..)));
+    let batches = StreamReader::new(file, metadata, None).map(|state| match state.unwrap() {
+        StreamState::Some(batch) => batch.columns()[2].clone(),
+        StreamState::Waiting => panic!("the stream is complete"),
+    });
+    assert_eq!(names(batches), expected);
+}
+
+#[test]
+fn missing_inputs_fail() {
+    let output = tempfile::Builder::new().suffix(".arrow").tempfile().unwrap();
+    let output = output.path().to_str().unwrap();
+    assert!(sas_to_ipc("/nonexistent/file.sas7bdat", output, SasIpcOptions::new()).is_err());
+}
@@ -0,0 +1,174 @@
+use cpp_sas7bdat::{SasCatalog, SasRange, SasReadOptions, SasReader, SasValueLabelMode, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+// Schools coded with formats of the `formats.sas7bcat` catalog
+fn schools() -> tempfile::NamedTempFile {
+    let df = df!(
+        "ID" => [1.0, 2.0, 3.0, 4.0, 5.0],
+        "LEVEL" => [Some(1.0), Some(3.0), None, Some(2.0), Some(7.0)],
+        "FRAME" => ["AREA", "LIST", "LIST", "", "AREA"],
+        "SCORE" => [0.5, -2.0, 12.0, 99.0, 3.0],
+    ).unwrap();
+    let options = SasWriteOptions::new()
+        .with_format("LEVEL", "LEVELF.")
+        .with_format("FRAME", "$FRAMEF.")
+        .with_format("SCORE", "SCOREF.");
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options).unwrap();
+    writer.write_batch(&df.slice(0, 2)).unwrap();
+    writer.write_batch(&df.slice(2, 3)).unwrap();
+    writer.finish().unwrap();
+    file
+}
+
+fn strings(column: &Column) -> Vec<Option<String>> {
+    let column = column.cast(&DataType::String).unwrap();
+    column.as_materialized_series().str().unwrap().iter().map(|value| value.map(str::to_string)).collect()
+}
+
+fn some(values: &[&str]) -> Vec<Option<String>> {
+    values.iter().map(|value| Some(value.to_string())).collect()
+}
+
+#[test]
+fn catalog_labels_replace_the_values() {
+    let catalog = SasCatalog::open(&test_file("data_gov/formats.sas7bcat")).unwrap();
+    let file = schools();
+    let path = file.path().to_str().unwrap();
+    let options = SasReadOptions::new().with_chunk_size(2).with_catalog(&catalog);
+
+    let mut reader = SasReader::open(path, options.clone()).unwrap();
+    let schema = reader.get_schema().unwrap().clone();
+    assert!(matches!(schema.get("LEVEL"), Some(DataType::Categorical(..))));
+    assert!(matches!(schema.get("FRAME"), Some(DataType::Categorical(..))));
+    assert_eq!(schema.get("SCORE"), Some(&DataType::Float64));
+
+    let df = read_all(path, options).unwrap();
+    assert_eq!(strings(df.column("LEVEL").unwrap()), [
+        Some("Elementary".to_string()),
+        Some("Combined elementary and secondary".to_string()),
+        None,
+        Some("Secondary".to_string()),
+        Some("7".to_string()),
+    ]);
+    let (area, list) = ("School is part of area frame", "School is part of list frame");
+    assert_eq!(strings(df.column("FRAME").unwrap()), some(&[area, list, list, "", area]));
+}
+
+#[test]
+fn ranges_other_and_missing_values_are_labelled() {
+    let file = schools();
+    let path = file.path().to_str().unwrap();
+    let score = vec![
+        (SasRange::Numbers(None, Some(0.0)), "Negative".to_string()),
+        (SasRange::Numbers(Some(0.0), Some(10.0)), "Low".to_string()),
+        (SasRange::Numbers(Some(10.0), None), "High".to_string()),
+    ];
+    let level = vec![
+        (SasRange::Numbers(Some(1.0), Some(2.0)), "Single level".to_string()),
+        (SasRange::Missing('.'), "Unknown".to_string()),
+        (SasRange::Other, "Other".to_string()),
+    ];
+    let frame = vec![(SasRange::Strings(Some("A".to_string()), Some("B".to_string())), "A to B".to_string())];
+    let options = SasReadOptions::new()
+        .with_value_labels("scoref", score)
+        .with_value_labels("LEVELF", level)
+        .with_value_labels("$FRAMEF", frame)
+        .with_value_label_mode(SasValueLabelMode::Companion);
+
+    let df = read_all(path, options).unwrap();
+    assert_eq!(
+        df.get_column_names_str(),
+        ["ID", "LEVEL", "LEVEL_label", "FRAME", "FRAME_label", "SCORE", "SCORE_label"],
+    );
+    assert_eq!(df.column("LEVEL").unwrap().dtype(), &DataType::Float64);
+    // The first range holding a value labels it
+    assert_eq!(strings(df.column("SCORE_label").unwrap()), some(&["Low", "Negative", "High", "High", "Low"]));
+    assert_eq!(
+        strings(df.column("LEVEL_label").unwrap()),
+        some(&["Single level", "Other", "Unknown", "Single level", "Other"]),
+    );
+    assert_eq!(strings(df.column("FRAME_label").unwrap()), some(&["A to B", "LIST", "LIST", "", "A to B"]));
+}
+
+#[test]
+fn special_missing_values_are_labelled() {
+    // The level 7 of the schools becomes the special missing value `.A`
+    let file = schools();
+    let path = file.path().to_str().unwrap();
+    let mut bytes = std::fs::read(path).unwrap();
+    let seven = 7.0f64.to_le_bytes();
+    let at = bytes.windows(8).position(|value| value == seven).unwrap();
+    bytes[at..at + 8].copy_from_slice(&0xFFFF_FD00_0000_0000u64.to_le_bytes());
+    std::fs::write(path, bytes).unwrap();
+
+    let level = vec![
+        (SasRange::Missing('.'), "Unknown".to_string()),
+        (SasRange::Missing('A'), "Refused".to_string()),
+    ];
+    let options = SasReadOptions::new().with_value_labels("LEVELF", level);
+    let df = read_all(path, options.clone()).unwrap();
+    assert_eq!(strings(df.column("LEVEL").unwrap()), some(&["1", "3", "Unknown", "2", "Refused"]));
+
+    let df = read_all(path, options.with_value_label_mode(SasValueLabelMode::Companion)).unwrap();
+    assert_eq!(strings(df.column("LEVEL_label").unwrap()), some(&["1", "3", "Unknown", "2", "Refused"]));
+    let levels = df.column("LEVEL").unwrap().as_materialized_series().f64().unwrap().to_vec();
+    assert_eq!(levels, [Some(1.0), Some(3.0), None, Some(2.0), None]);
+}
+
+#[test]
+fn label_columns_must_not_exist() {
+    let df = df!("LEVEL" => [1.0], "LEVEL_label" => ["one"]).unwrap();
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let path = file.path().to_str().unwrap();
+    let mut writer = SasWriter::create(path, SasWriteOptions::new().with_format("LEVEL", "LEVELF.")).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+
+    let options = SasReadOptions::new()
+        .with_value_labels("LEVELF", vec![(SasRange::Numbers(Some(1.0), Some(1.0)), "One".to_string())])
+        .with_value_label_mode(SasValueLabelMode::Companion);
+    let mut reader = SasReader::open(path, options).unwrap();
+    assert!(matches!(reader.get_schema(), Err(PolarsError::Duplicate(_))));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_value_labels() {
+    let catalog = SasCatalog::from_json(
+        r#"{"levelf": {"1-2": "Single level", ".": "Unknown", "OTHER": "Other"}, "$FRAMEF": {"AREA": "Area"}}"#,
+    )
+    .unwrap();
+    assert_eq!(catalog.get("LEVELF").unwrap().len(), 3);
+    assert!(catalog.get("LEVELF").unwrap().contains(&(SasRange::Missing('.'), "Unknown".to_string())));
+    assert_eq!(catalog.get("$FRAMEF").unwrap(), [(
+        SasRange::Strings(Some("AREA".to_string()), Some("AREA".to_string())),
+        "Area".to_string(),
+    )]);
+
+    let file = schools();
+    let df = read_all(file.path().to_str().unwrap(), SasReadOptions::new().with_catalog(&catalog)).unwrap();
+    assert_eq!(
+        strings(df.column("LEVEL").unwrap()),
+        some(&["Single level", "Other", "Unknown", "Single level", "Other"]),
+    );
+    assert_eq!(strings(df.column("FRAME").unwrap()), some(&["Area", "LIST", "LIST", "", "Area"]));
+
+    // The first range of the object holding a value labels it
+    let catalog = SasCatalog::from_json(r#"{"LEVELF": {"3": "Three", "1-5": "One to five"}}"#).unwrap();
+    assert_eq!(catalog.get("LEVELF").unwrap(), [
+        (SasRange::Numbers(Some(3.0), Some(3.0)), "Three".to_string()),
+        (SasRange::Numbers(Some(1.0), Some(5.0)), "One to five".to_string()),
+    ]);
+    let df = read_all(file.path().to_str().unwrap(), SasReadOptions::new().with_catalog(&catalog)).unwrap();
+    assert_eq!(
+        strings(df.column("LEVEL").unwrap()),
+        [Some("One to five"), Some("Three"), None, Some("One to five"), Some("7")].map(|v| v.map(str::to_string)),
+    );
+
+    assert!(SasCatalog::from_json(r#"{"LEVELF": {"1-x": "Bad"}}"#).is_err());
+    assert!(SasCatalog::from_json(r#"{"LEVELF": ["1"]}"#).is_err());
+}
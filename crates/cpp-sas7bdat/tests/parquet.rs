@@ -0,0 +1,104 @@
+#![cfg(feature = "parquet")]
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use cpp_sas7bdat::{sas_to_parquet, SasParquetOptions, SasReadOptions, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+fn sample() -> (DataFrame, tempfile::NamedTempFile) {
+    let df = df!(
+        "ID" => [1.0, 2.0, 3.0, 4.0, 5.0],
+        "AMOUNT" => [Some(1234.5), None, Some(-0.25), Some(0.0), Some(99.0)],
+        "NAME" => ["one", "two", "three", "four", "five"],
+    ).unwrap();
+    let options = SasWriteOptions::new()
+        .with_dataset_name("SAMPLE")
+        .with_label("AMOUNT", "Amount paid")
+        .with_format("AMOUNT", "DOLLAR10.2");
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+    (df, file)
+}
+
+fn convert(input: &tempfile::NamedTempFile, options: SasParquetOptions) -> tempfile::NamedTempFile {
+    let output = tempfile::Builder::new().suffix(".parquet").tempfile().unwrap();
+    sas_to_parquet(input.path().to_str().unwrap(), output.path().to_str().unwrap(), options).unwrap();
+    output
+}
+
+fn row_group_sizes(reader: &mut ParquetReader<File>) -> Vec<usize> {
+    let metadata = reader.get_metadata().unwrap();
+    metadata.row_groups.iter().map(|row_group| row_group.num_rows()).collect()
+}
+
+#[test]
+fn batches_are_written_as_row_groups() {
+    let (df, input) = sample();
+    let options = SasParquetOptions::new().with_read_options(SasReadOptions::new().with_chunk_size(2));
+    let output = convert(&input, options);
+
+    let mut reader = ParquetReader::new(File::open(output.path()).unwrap());
+    assert_eq!(row_group_sizes(&mut reader), [2, 2, 1]);
+    assert!(reader.finish().unwrap().equals_missing(&df));
+}
+
+#[test]
+fn batches_are_regrouped_by_row_count() {
+    let (df, input) = sample();
+    let options = SasParquetOptions::new()
+        .with_read_options(SasReadOptions::new().with_chunk_size(2))
+        .with_row_group_size(3)
+        .with_compression(ParquetCompression::Snappy)
+        .with_statistics(false);
+    let output = convert(&input, options);
+
+    let mut reader = ParquetReader::new(File::open(output.path()).unwrap());
+    assert_eq!(row_group_sizes(&mut reader), [3, 2]);
+    assert!(reader.finish().unwrap().equals_missing(&df));
+}
+
+#[test]
+fn zero_row_group_size_writes_the_batches() {
+    let (df, input) = sample();
+    // Set directly, bypassing the clamp of the builder
+    let options = SasParquetOptions {
+        row_group_size: Some(0),
+        ..SasParquetOptions::new().with_read_options(SasReadOptions::new().with_chunk_size(2))
+    };
+    let output = convert(&input, options);
+
+    let mut reader = ParquetReader::new(File::open(output.path()).unwrap());
+    assert_eq!(row_group_sizes(&mut reader), [2, 2, 1]);
+    assert!(reader.finish().unwrap().equals_missing(&df));
+}
+
+#[test]
+fn sas_metadata_is_kept() {
+    let (_, input) = sample();
+    let output = convert(&input, SasParquetOptions::new());
+
+    let mut reader = ParquetReader::new(File::open(output.path()).unwrap());
+    let metadata = reader.get_metadata().unwrap();
+    let key_value_metadata: HashMap<String, String> = metadata
+        .key_value_metadata
+        .iter()
+        .flatten()
+        .filter_map(|key_value| Some((key_value.key.clone(), key_value.value.clone()?)))
+        .collect();
+    assert_eq!(key_value_metadata["sas.dataset_name"], "SAMPLE");
+    assert_eq!(key_value_metadata["sas.row_count"], "5");
+    assert_eq!(key_value_metadata["sas.label.AMOUNT"], "Amount paid");
+    assert_eq!(key_value_metadata["sas.format.AMOUNT"], "DOLLAR10.2");
+    assert!(!key_value_metadata.contains_key("sas.label.ID"));
+    assert!(!key_value_metadata.contains_key("sas.dataset_label"));
+}
+
+#[test]
+fn missing_inputs_fail() {
+    let output = tempfile::Builder::new().suffix(".parquet").tempfile().unwrap();
+    let output = output.path().to_str().unwrap();
+    assert!(sas_to_parquet("/nonexistent/file.sas7bdat", output, SasParquetOptions::new()).is_err());
+}
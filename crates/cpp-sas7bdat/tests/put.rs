@@ -0,0 +1,109 @@
+use cpp_sas7bdat::{sas_put, SasBatchIterator, SasReadOptions, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+// 1991-10-17 and 1991-10-17T14:45:32 as SAS values
+const DATE: f64 = 11612.0;
+const DATETIME: f64 = 1_003_329_932.0;
+
+// Value, format and the output of the SAS `PUT` function
+const CONFORMANCE: &[(Option<f64>, &str, &str)] = &[
+    (Some(1234567.891), "COMMA12.2", "1,234,567.89"),
+    (Some(-1234.5), "COMMA12.2", "   -1,234.50"),
+    (Some(999.5), "COMMA6.", " 1,000"),
+    (Some(12.0), "COMMA.", "    12"),
+    (Some(1234.5), "DOLLAR10.", "    $1,235"),
+    (Some(1234.567), "DOLLAR10.2", " $1,234.57"),
+    (Some(-1234.567), "DOLLAR10.2", "-$1,234.57"),
+    (Some(0.1234), "PERCENT8.1", "  12.3% "),
+    (Some(-0.1), "PERCENT8.1", " (10.0%)"),
+    (Some(0.5), "PERCENT.", "  50% "),
+    (Some(42.0), "Z5.", "00042"),
+    (Some(-42.0), "Z5.", "-0042"),
+    (Some(3.14159), "Z8.2", "00003.14"),
+    (Some(DATE), "DATE9.", "17OCT1991"),
+    (Some(DATE), "DATE7.", "17OCT91"),
+    (Some(DATE), "DATE5.", "17OCT"),
+    (Some(DATE), "DATE11.", "17-OCT-1991"),
+    (Some(0.0), "DATE9.", "01JAN1960"),
+    (Some(-1.0), "DATE9.", "31DEC1959"),
+    (Some(DATE), "MMDDYY10.", "10/17/1991"),
+    (Some(DATE), "MMDDYY8.", "10/17/91"),
+    (Some(DATE), "MMDDYY6.", "101791"),
+    (Some(DATETIME), "DATETIME20.", "  17OCT1991:14:45:32"),
+    (Some(DATETIME), "DATETIME.", "17OCT91:14:45:32"),
+    (Some(DATETIME), "DATETIME7.", "17OCT91"),
+    (Some(DATETIME + 0.25), "DATETIME18.1", "17OCT91:14:45:32.2"),
+    (Some(52_200.0), "TIME8.", "14:30:00"),
+    (Some(3_600.0), "TIME8.", " 1:00:00"),
+    (Some(1234.5678), "BEST12.", "   1234.5678"),
+    (Some(1.0 / 3.0), "BEST12.", "0.3333333333"),
+    (Some(42.0), "BEST12.", "          42"),
+    (Some(0.0), "BEST12.", "           0"),
+    (Some(1e15), "BEST12.", "        1E15"),
+    (Some(123_456_789_012_345.0), "BEST12.", "1.2345679E14"),
+    (Some(-123_456_789_012_345.0), "BEST12.", "-1.234568E14"),
+    (Some(1e-20), "BEST12.", "       1E-20"),
+    (Some(12345.0), "BEST4.", "12E3"),
+    (Some(2.5), "1.", "3"),
+    (Some(1.005), "8.2", "    1.01"),
+    (Some(-2.345), "8.2", "   -2.35"),
+    (Some(1234.5678), "F8.2", " 1234.57"),
+    (Some(-2.5), "F4.", "  -3"),
+    (Some(42.0), "F.", "          42"),
+    (Some(-0.125), "f6.2", " -0.13"),
+    // Too wide for the format
+    (Some(1234567.0), "COMMA6.", "1.23E6"),
+    (Some(1e9), "DATE9.", "*********"),
+    (None, "COMMA12.2", "           ."),
+    (None, "DATE9.", "        ."),
+    (Some(f64::NAN), "BEST12.", "           ."),
+];
+
+#[test]
+fn values_are_rendered_as_sas_does() {
+    for (value, format, expected) in CONFORMANCE {
+        assert_eq!(sas_put(*value, format).unwrap(), *expected, "{:?} {}", value, format);
+    }
+}
+
+#[test]
+fn invalid_formats_fail() {
+    for format in ["FOO8.", "DATE20.", "COMMA40.", ".", "5.5"] {
+        assert!(sas_put(Some(1.0), format).is_err(), "{}", format);
+    }
+}
+
+#[test]
+fn columns_are_read_as_formatted_strings() {
+    let df = df!(
+        "AMOUNT" => [Some(1234.5), None, Some(-0.25)],
+        "VISIT" => [DATE as i32 - 3653, 0, 1],
+        "CODE" => [7.0, 42.0, 123456.0],
+        "RAW" => [0.5, 1.0 / 3.0, 1e15],
+    ).unwrap();
+    let df = df.lazy().with_column(col("VISIT").cast(DataType::Date)).collect().unwrap();
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let path = file.path().to_str().unwrap();
+    let options = SasWriteOptions::new().with_format("AMOUNT", "DOLLAR10.2").with_format("VISIT", "MMDDYY10.");
+    let mut writer = SasWriter::create(path, options).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+
+    let options = SasReadOptions::new()
+        .with_formatted_column("AMOUNT")
+        .with_formatted_column("VISIT")
+        .with_put_format("CODE", "Z5.")
+        .with_formatted_column("RAW");
+    let batches: Vec<DataFrame> = SasBatchIterator::open(path, options).unwrap().map(Result::unwrap).collect();
+    let strings = |name: &str| -> Vec<String> {
+        let column = batches[0].column(name).unwrap();
+        column.as_materialized_series().str().unwrap().into_no_null_iter().map(str::to_string).collect()
+    };
+    assert_eq!(strings("AMOUNT"), ["$1,234.50", ".", "-$0.25"]);
+    assert_eq!(strings("VISIT"), ["10/17/1991", "01/01/1970", "01/02/1970"]);
+    assert_eq!(strings("CODE"), ["00007", "00042", "1.2E5"]);
+    assert_eq!(strings("RAW"), ["0.5", "0.3333333333", "1E15"]);
+
+    let missing = SasReadOptions::new().with_formatted_column("OTHER");
+    assert!(SasBatchIterator::open(path, missing).and_then(|mut batches| batches.schema().cloned()).is_err());
+}
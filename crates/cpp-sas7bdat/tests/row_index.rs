@@ -0,0 +1,104 @@
+use cpp_sas7bdat::{SasBatch, SasBatchIterator, SasParallelBatchIterator, SasReadOptions, SasReader, SasRowIndexType};
+use polars::prelude::*;
+
+mod common;
+use common::test_file;
+
+fn collect(batches: impl Iterator<Item = PolarsResult<SasBatch>>) -> PolarsResult<(DataFrame, Vec<SasBatch>)> {
+    let mut df: Option<DataFrame> = None;
+    let mut positions = Vec::new();
+    for batch in batches {
+        let batch = batch?;
+        match df.as_mut() {
+            Some(df) => {
+                df.vstack_mut(&batch.df)?;
+            }
+            None => df = Some(batch.df.clone()),
+        }
+        positions.push(batch);
+    }
+    Ok((df.unwrap_or_default(), positions))
+}
+
+#[test]
+fn row_index_numbers_the_rows_from_the_offset() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let expected = SasBatchIterator::new(&path, Some(1000))
+        .and_then(|batches| collect(batches.sas_batches()))
+        .unwrap()
+        .0;
+
+    for dtype in [SasRowIndexType::UInt32, SasRowIndexType::UInt64] {
+        let options = SasReadOptions::new().with_chunk_size(1000).with_row_index("row_nr", 10, dtype);
+        let mut batches = SasBatchIterator::with_options(&path, options).unwrap();
+        let schema = batches.schema().unwrap().clone();
+        let (df, _) = collect(batches.sas_batches()).unwrap();
+
+        let expected_dtype = match dtype {
+            SasRowIndexType::UInt32 => DataType::UInt32,
+            SasRowIndexType::UInt64 => DataType::UInt64,
+        };
+        assert_eq!(schema.get_at_index(0), Some((&"row_nr".into(), &expected_dtype)));
+        assert_eq!(df.schema().as_ref(), &schema);
+
+        let row_nr = df.column("row_nr").unwrap().cast(&DataType::UInt64).unwrap();
+        let row_nr: Vec<u64> = row_nr.u64().unwrap().into_no_null_iter().collect();
+        assert_eq!(row_nr, (10..10 + expected.height() as u64).collect::<Vec<_>>());
+        assert!(df.drop("row_nr").unwrap().equals_missing(&expected));
+    }
+}
+
+#[test]
+fn batches_locate_their_rows() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let (first_data_page, page_count) = SasReader::new(&path, None).unwrap().page_range().unwrap();
+    let (df, batches) = SasBatchIterator::new(&path, Some(1000))
+        .and_then(|batches| collect(batches.sas_batches()))
+        .unwrap();
+    assert!(batches.len() > 1);
+
+    let mut rows = 0;
+    let mut previous_page = first_data_page;
+    for batch in &batches {
+        assert_eq!(batch.first_row, rows);
+        assert!(!batch.page_range.is_empty());
+        assert!(batch.page_range.start >= previous_page);
+        assert!(batch.page_range.end <= page_count);
+        rows += batch.df.height() as u64;
+        previous_page = batch.page_range.end - 1;
+    }
+    assert_eq!(rows, df.height() as u64);
+    assert_eq!(batches[0].page_range.start, first_data_page);
+}
+
+#[test]
+fn parallel_reads_number_the_rows_as_sequential_reads() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let options = SasReadOptions::new().with_chunk_size(1000).with_row_index("row_nr", 1, SasRowIndexType::UInt64);
+
+    let (expected, _) = SasBatchIterator::with_options(&path, options.clone())
+        .and_then(|batches| collect(batches.sas_batches()))
+        .unwrap();
+    let (df, batches) = SasParallelBatchIterator::new(&path, options)
+        .and_then(|batches| collect(batches.sas_batches()))
+        .unwrap();
+
+    assert!(df.equals_missing(&expected));
+    let mut rows = 0;
+    for batch in &batches {
+        assert_eq!(batch.first_row, rows);
+        assert_eq!(batch.df.column("row_nr").unwrap().u64().unwrap().get(0), Some(rows + 1));
+        rows += batch.df.height() as u64;
+    }
+}
+
+#[test]
+fn row_index_names_must_be_new() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let mut reader = SasReader::new(&path, None).unwrap();
+    let name = reader.get_schema().unwrap().get_at_index(0).unwrap().0.to_string();
+
+    let options = SasReadOptions::new().with_row_index(&name, 0, SasRowIndexType::UInt64);
+    let mut reader = SasReader::with_options(&path, options).unwrap();
+    assert!(matches!(reader.get_schema(), Err(PolarsError::Duplicate(_))));
+}
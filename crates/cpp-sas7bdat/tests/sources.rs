@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+use cpp_sas7bdat::{SasBatchIterator, SasParallelBatchIterator, SasReadOptions, SasRowIndexType};
+
+mod common;
+use common::{assert_reads_match_path_reads, collect, read_path, readable_test_files, test_file};
+
+#[test]
+fn from_bytes_matches_path_reads() {
+    assert_reads_match_path_reads(&readable_test_files(), 1000, |path| {
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        SasBatchIterator::from_bytes(std::fs::read(path)?, options).and_then(collect)
+    });
+}
+
+#[test]
+fn from_reader_matches_path_reads() {
+    let files = readable_test_files();
+    assert_reads_match_path_reads(&files[..20], 1000, |path| {
+        let cursor = Cursor::new(std::fs::read(path)?);
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        SasBatchIterator::from_reader(cursor, options).and_then(collect)
+    });
+}
+
+#[test]
+fn mmap_matches_path_reads() {
+    assert_reads_match_path_reads(&readable_test_files(), 1000, |path| {
+        let options = SasReadOptions::new()
+            .with_chunk_size(1000)
+            .with_mmap(true)
+            .with_sequential_access(true);
+        SasBatchIterator::with_options(path, options).and_then(collect)
+    });
+}
+
+#[test]
+fn parallel_reads_match_path_reads() {
+    for path in readable_test_files() {
+        let expected = read_path(&path, 1000);
+
+        let options = SasReadOptions::new().with_chunk_size(1000);
+        let df = SasParallelBatchIterator::new(&path, options)
+            .and_then(collect)
+            .unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+        // No batch at all for a file without rows
+        assert_eq!(df.height(), expected.height(), "{}", path);
+        if expected.height() > 0 {
+            assert!(df.equals_missing(&expected), "{}: rows differ", path);
+        }
+    }
+}
+
+#[test]
+fn prefetched_batches_match_path_reads() {
+    let files = readable_test_files();
+    assert_reads_match_path_reads(&files[..40], 100, |path| {
+        SasBatchIterator::new(path, Some(100))?.prefetch(2).and_then(collect)
+    });
+}
+
+#[test]
+fn dropping_a_prefetch_iterator_stops_the_background_thread() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let mut batches = SasBatchIterator::new(&path, Some(10)).unwrap().prefetch(1).unwrap();
+    assert!(batches.next().unwrap().is_ok());
+    // Returns once the background thread has released the reader
+    drop(batches);
+}
+
+#[test]
+fn parallel_reads_stream_the_batches_of_each_range() {
+    // Many more batches per range than the queue of a range holds
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let expected = read_path(&path, 10);
+    let mut batches = SasParallelBatchIterator::new(&path, SasReadOptions::new().with_chunk_size(10)).unwrap();
+    let first = batches.next().unwrap().unwrap();
+    assert!(first.equals_missing(&expected.slice(0, first.height())));
+
+    let rest = collect(batches).unwrap();
+    assert_eq!(first.height() + rest.height(), expected.height());
+    assert!(first.vstack(&rest).unwrap().equals_missing(&expected));
+}
+
+#[test]
+fn dropping_a_parallel_iterator_stops_its_threads() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let mut batches = SasParallelBatchIterator::new(&path, SasReadOptions::new().with_chunk_size(10)).unwrap();
+    assert!(batches.next().unwrap().is_ok());
+    // Returns once every thread has released its reader
+    drop(batches);
+}
+
+#[test]
+fn parallel_reads_skip_and_limit_the_rows() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let expected = read_path(&path, 10);
+    let rows = expected.height() as u64;
+    for (skip_rows, n_rows) in [(0, 0), (0, 25), (15, 30), (rows - 5, 100), (rows + 1, 10)] {
+        let options = SasReadOptions::new()
+            .with_chunk_size(10)
+            .with_skip_rows(skip_rows)
+            .with_n_rows(n_rows)
+            .with_row_index("ROW", 0, SasRowIndexType::UInt64);
+        let sequential = collect(SasBatchIterator::with_options(&path, options.clone()).unwrap()).unwrap();
+        let parallel = collect(SasParallelBatchIterator::new(&path, options).unwrap()).unwrap();
+
+        let height = n_rows.min(rows.saturating_sub(skip_rows)) as usize;
+        assert_eq!(sequential.height(), height, "{skip_rows} {n_rows}");
+        if height > 0 {
+            let data = sequential.drop("ROW").unwrap();
+            assert!(data.equals_missing(&expected.slice(skip_rows as i64, height)));
+            assert_eq!(sequential.column("ROW").unwrap().u64().unwrap().get(0), Some(skip_rows));
+            assert!(parallel.equals_missing(&sequential), "{skip_rows} {n_rows}");
+        }
+    }
+}
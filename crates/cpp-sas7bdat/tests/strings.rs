@@ -0,0 +1,20 @@
+use cpp_sas7bdat::{SasReadOptions, SasStringLayout};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+#[test]
+fn string_layouts_read_the_same_values() {
+    let path = test_file("data_pandas/productsales.sas7bdat");
+    let plain = read_all(&path, SasReadOptions::new()).unwrap();
+
+    let views = read_all(&path, SasReadOptions::new().with_string_layout(SasStringLayout::Utf8View)).unwrap();
+    assert_eq!(views.column("PRODUCT").unwrap().dtype(), &DataType::String);
+    assert!(views.equals_missing(&plain));
+
+    let binary = read_all(&path, SasReadOptions::new().with_string_layout(SasStringLayout::BinaryView)).unwrap();
+    let products = binary.column("PRODUCT").unwrap();
+    assert_eq!(products.dtype(), &DataType::Binary);
+    assert!(products.cast(&DataType::String).unwrap().equals_missing(plain.column("PRODUCT").unwrap()));
+}
@@ -0,0 +1,153 @@
+use cpp_sas7bdat::{SasReadOptions, SasReader, SasTemporalType, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+// Columns desc (no format), mtg (DATE), dt (DATETIME) and tm (TIME)
+fn meetings() -> String {
+    test_file("data_pandas/datetime.sas7bdat")
+}
+
+// 1991-10-17 and 1991-10-17T14:45:32 as SAS values, 14:30 as seconds
+const DATE: f64 = 11612.0;
+const DATETIME: f64 = 1_003_329_932.0;
+const TIME: f64 = 52_200.0;
+
+// Numeric columns with a date, a datetime, a time, a user and a plain number format
+fn formatted() -> tempfile::NamedTempFile {
+    let df = df!(
+        "VISIT" => [DATE],
+        "STAMP" => [DATETIME],
+        "CLOCK" => [TIME],
+        "CUSTOM" => [DATE],
+        "AMOUNT" => [DATE],
+    ).unwrap();
+    let options = SasWriteOptions::new()
+        .with_format("VISIT", "YYMMDD10.")
+        .with_format("STAMP", "TOD8.")
+        .with_format("CLOCK", "HHMM5.")
+        .with_format("CUSTOM", "MYDATE9.")
+        .with_format("AMOUNT", "COMMA12.2");
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options).unwrap();
+    writer.write_batch(&df).unwrap();
+    writer.finish().unwrap();
+    file
+}
+
+fn types(path: &str, options: SasReadOptions) -> Vec<DataType> {
+    let mut reader = SasReader::with_options(path, options).unwrap();
+    reader.get_schema().unwrap().iter_values().cloned().collect()
+}
+
+const DATETIME_TYPE: DataType = DataType::Datetime(TimeUnit::Microseconds, None);
+
+#[test]
+fn builtin_formats_are_detected() {
+    let path = meetings();
+    assert_eq!(types(&path, SasReadOptions::new()), [
+        DataType::String,
+        DataType::Date,
+        DATETIME_TYPE,
+        DataType::Time,
+    ]);
+
+    let df = read_all(&path, SasReadOptions::new()).unwrap();
+    let value = |name: &str, row| df.column(name).unwrap().get(row).unwrap().into_static();
+    // 2017-11-24, 2018-03-31T14:20:33 and 00:00:05
+    assert_eq!(value("mtg", 0), AnyValue::Date(17_494));
+    assert_eq!(value("dt", 4), AnyValue::Datetime(1_522_506_033_000_000, TimeUnit::Microseconds, None));
+    assert_eq!(value("tm", 1), AnyValue::Time(5_000_000_000));
+}
+
+#[test]
+fn user_formats_take_precedence() {
+    let path = meetings();
+    let options = SasReadOptions::new()
+        .with_temporal_format("date", SasTemporalType::Number)
+        .with_temporal_format("TIME", SasTemporalType::Datetime);
+    assert_eq!(types(&path, options.clone()), [DataType::String, DataType::Float64, DATETIME_TYPE, DATETIME_TYPE]);
+
+    // Days since 1960-01-01
+    let df = read_all(&path, options).unwrap();
+    assert_eq!(df.column("mtg").unwrap().get(0).unwrap(), AnyValue::Float64(21_147.0));
+}
+
+#[test]
+fn detection_can_be_disabled() {
+    let path = meetings();
+    let options = SasReadOptions::new().with_temporal_detection(false);
+    assert_eq!(types(&path, options.clone()), [
+        DataType::String,
+        DataType::Float64,
+        DataType::Float64,
+        DataType::Float64,
+    ]);
+
+    // The user formats still apply
+    let options = options.with_temporal_format("DATETIME", SasTemporalType::Datetime);
+    assert_eq!(types(&path, options), [DataType::String, DataType::Float64, DATETIME_TYPE, DataType::Float64]);
+}
+
+#[test]
+fn written_builtin_formats_are_detected() {
+    let file = formatted();
+    let path = file.path().to_str().unwrap();
+    assert_eq!(types(path, SasReadOptions::new()), [
+        DataType::Date,
+        // TOD columns hold datetimes, of which TOD writes the time of day
+        DATETIME_TYPE,
+        DataType::Time,
+        DataType::Float64,
+        DataType::Float64,
+    ]);
+
+    let df = read_all(path, SasReadOptions::new()).unwrap();
+    let value = |name: &str| df.column(name).unwrap().get(0).unwrap().into_static();
+    assert_eq!(value("VISIT"), AnyValue::Date(7959));
+    assert_eq!(value("STAMP"), AnyValue::Datetime(687_710_732_000_000, TimeUnit::Microseconds, None));
+    assert_eq!(value("CLOCK"), AnyValue::Time(52_200_000_000_000));
+}
+
+#[test]
+fn user_formats_take_precedence_over_written_formats() {
+    let file = formatted();
+    let path = file.path().to_str().unwrap();
+    let options = SasReadOptions::new()
+        .with_temporal_format("mydate", SasTemporalType::Date)
+        .with_temporal_format("YYMMDD", SasTemporalType::Number)
+        .with_temporal_format("COMMA", SasTemporalType::Datetime);
+    assert_eq!(types(path, options.clone()), [
+        DataType::Float64,
+        DATETIME_TYPE,
+        DataType::Time,
+        DataType::Date,
+        DATETIME_TYPE,
+    ]);
+
+    let df = read_all(path, options).unwrap();
+    assert_eq!(df.column("VISIT").unwrap().get(0).unwrap(), AnyValue::Float64(DATE));
+    assert_eq!(df.column("CUSTOM").unwrap().get(0).unwrap(), AnyValue::Date(7959));
+}
+
+#[test]
+fn user_formats_apply_to_written_files_without_detection() {
+    let file = formatted();
+    let path = file.path().to_str().unwrap();
+    assert_eq!(types(path, SasReadOptions::new().with_temporal_detection(false)), [DataType::Float64; 5]);
+
+    // The user formats still apply
+    let options = SasReadOptions::new()
+        .with_temporal_detection(false)
+        .with_temporal_format("MYDATE", SasTemporalType::Date);
+    assert_eq!(types(path, options.clone()), [
+        DataType::Float64,
+        DataType::Float64,
+        DataType::Float64,
+        DataType::Date,
+        DataType::Float64,
+    ]);
+    let df = read_all(path, options).unwrap();
+    assert_eq!(df.column("STAMP").unwrap().get(0).unwrap(), AnyValue::Float64(DATETIME));
+}
@@ -0,0 +1,235 @@
+use cpp_sas7bdat::{SasCompression, SasReadOptions, SasReader, SasStringLayout, SasWriteOptions, SasWriter};
+use polars::prelude::*;
+
+mod common;
+use common::{read_all, test_file};
+
+fn write(batches: &[DataFrame], options: SasWriteOptions) -> PolarsResult<tempfile::NamedTempFile> {
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let mut writer = SasWriter::create(file.path().to_str().unwrap(), options)?;
+    for batch in batches {
+        writer.write_batch(batch)?;
+    }
+    writer.finish()?;
+    Ok(file)
+}
+
+fn sample(rows: std::ops::Range<i64>) -> DataFrame {
+    let df = df!(
+        "ID" => rows.clone().collect::<Vec<_>>(),
+        "WEIGHT" => rows.clone().map(|i| (i % 5 != 0).then_some(i as f64 * 1.5)).collect::<Vec<_>>(),
+        "NAME" => rows.clone().map(|i| (i % 3 != 0).then(|| format!("n{}", i))).collect::<Vec<_>>(),
+        "VISIT" => rows.clone().map(|i| i as i32 * 7 - 3653).collect::<Vec<_>>(),
+        "TAKEN" => rows.clone().map(|i| i * 3_600_000_000 + 1_700_000_000_000_000).collect::<Vec<_>>(),
+        "AT" => rows.clone().map(|i| (i % 86_400) * 1_000_000_000).collect::<Vec<_>>(),
+    ).unwrap();
+    df.lazy().with_columns([
+        col("VISIT").cast(DataType::Date),
+        col("TAKEN").cast(DataType::Datetime(TimeUnit::Microseconds, None)),
+        col("AT").cast(DataType::Time),
+    ]).collect().unwrap()
+}
+
+#[test]
+fn written_batches_read_back() {
+    let batches = [sample(0..700), sample(700..1000)];
+    let options = SasWriteOptions::new()
+        .with_dataset_name("SAMPLE")
+        .with_label("WEIGHT", "Weight (kg)")
+        .with_format("WEIGHT", "COMMA8.1")
+        .with_string_length("NAME", 8)
+        .with_page_length(4096);
+    let file = write(&batches, options).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let mut reader = SasReader::open(path, SasReadOptions::new()).unwrap();
+    let (first_data_page, page_count) = reader.page_range().unwrap();
+    assert!(page_count - first_data_page > 1);
+    let metadata = reader.metadata().unwrap();
+    assert_eq!(metadata.dataset_name, "SAMPLE");
+    assert_eq!(metadata.encoding, "UTF-8");
+    assert_eq!(metadata.row_count, Some(1000));
+    let columns: Vec<_> = metadata.columns.iter()
+        .map(|c| (c.name.as_str(), c.label.as_str(), c.format.as_str(), c.format_width, c.format_decimals, c.length))
+        .collect();
+    assert_eq!(columns, [
+        ("ID", "", "", 0, 0, 8),
+        ("WEIGHT", "Weight (kg)", "COMMA", 8, 1, 8),
+        ("NAME", "", "", 0, 0, 8),
+        ("VISIT", "", "DATE", 9, 0, 8),
+        ("TAKEN", "", "DATETIME", 20, 0, 8),
+        ("AT", "", "TIME", 8, 0, 8),
+    ]);
+
+    let df = read_all(path, SasReadOptions::new()).unwrap();
+    let expected = sample(0..1000).lazy().with_columns([
+        col("ID").cast(DataType::Float64),
+        col("NAME").fill_null(lit("")),
+    ]).collect().unwrap();
+    assert!(df.equals_missing(&expected), "{df} != {expected}");
+}
+
+#[test]
+fn compressed_rows_read_back() {
+    let expected = sample(0..1000).lazy().with_columns([
+        col("ID").cast(DataType::Float64),
+        col("NAME").fill_null(lit("")),
+    ]).collect().unwrap();
+    for compression in [SasCompression::Rle, SasCompression::Rdc] {
+        let options = SasWriteOptions::new()
+            .with_string_length("NAME", 40)
+            .with_page_length(4096)
+            .with_compression(compression);
+        let file = write(&[sample(0..600), sample(600..1000)], options).unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let metadata = SasReader::open(path, SasReadOptions::new()).unwrap().metadata().unwrap();
+        assert_eq!(metadata.row_count, Some(1000));
+        let df = read_all(path, SasReadOptions::new()).unwrap();
+        assert!(df.equals_missing(&expected), "{compression:?}: {df} != {expected}");
+    }
+}
+
+#[test]
+fn compression_shrinks_padded_and_repeated_values() {
+    let df = df!(
+        "CODE" => (0..2000).map(|i| i % 7).collect::<Vec<_>>(),
+        "TEXT" => (0..2000).map(|i| vec![format!("value {}", i); i % 15].join(" ")).collect::<Vec<_>>(),
+        "COMMENT" => (0..2000).map(|i| (i % 4 == 0).then(|| "@".repeat(300))).collect::<Vec<_>>(),
+    ).unwrap();
+    let expected = df.clone().lazy().with_columns([
+        col("CODE").cast(DataType::Float64),
+        col("COMMENT").fill_null(lit("")),
+    ]).collect().unwrap();
+
+    let size = |compression| {
+        let options = SasWriteOptions::new().with_string_length("TEXT", 1000).with_compression(compression);
+        let file = write(&[df.clone()], options).unwrap();
+        let read = read_all(file.path().to_str().unwrap(), SasReadOptions::new()).unwrap();
+        assert!(read.equals_missing(&expected), "{compression:?}: {read} != {expected}");
+        file.as_file().metadata().unwrap().len()
+    };
+    let uncompressed = size(SasCompression::None);
+    assert!(size(SasCompression::Rle) * 3 < uncompressed);
+    assert!(size(SasCompression::Rdc) * 3 < uncompressed);
+}
+
+#[test]
+fn compression_handles_long_runs_and_incompressible_rows() {
+    // Runs longer than the longest command of each codec
+    let runs = df!(
+        "ID" => (0..50).map(f64::from).collect::<Vec<_>>(),
+        "RUN" => (0..50).map(|i| match i % 5 {
+            0 => format!("<{}>", " ".repeat(9000)),
+            1 => "@".repeat(4500),
+            2 => "q".repeat(5000),
+            3 => "abc".repeat(2000),
+            _ => String::new(),
+        }).collect::<Vec<_>>(),
+    ).unwrap();
+    // Rows of distinct bytes, stored as they are
+    let mut state = 1u64;
+    let noise: Vec<Column> = (0..8)
+        .map(|c| {
+            let values: Vec<f64> = (0..50).map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                f64::from_bits((state >> 12) | 0x3FF0_0000_0000_0000)
+            }).collect();
+            Column::new(format!("X{}", c).into(), values)
+        })
+        .collect();
+    let noise = DataFrame::new(noise).unwrap();
+
+    for compression in [SasCompression::Rle, SasCompression::Rdc] {
+        for df in [&runs, &noise] {
+            let file = write(&[df.clone()], SasWriteOptions::new().with_compression(compression)).unwrap();
+            let read = read_all(file.path().to_str().unwrap(), SasReadOptions::new()).unwrap();
+            assert!(read.equals_missing(df), "{compression:?}: {read} != {df}");
+        }
+    }
+}
+
+#[test]
+fn files_are_copied_with_their_metadata() {
+    let path = test_file("data_AHS2013/homimp.sas7bdat");
+    let expected = read_all(&path, SasReadOptions::new()).unwrap();
+    let metadata = SasReader::open(&path, SasReadOptions::new()).unwrap().metadata().unwrap();
+
+    let file = write(&[expected.clone()], SasWriteOptions::new().with_metadata(&metadata)).unwrap();
+    let copy = file.path().to_str().unwrap();
+    assert!(read_all(copy, SasReadOptions::new()).unwrap().equals_missing(&expected));
+
+    let copied = SasReader::open(copy, SasReadOptions::new()).unwrap().metadata().unwrap();
+    assert_eq!(copied.dataset_name, metadata.dataset_name);
+    assert_eq!(copied.row_count, metadata.row_count);
+    for (copied, column) in copied.columns.iter().zip(&metadata.columns) {
+        assert_eq!((&copied.name, &copied.label, &copied.format), (&column.name, &column.label, &column.format));
+    }
+}
+
+#[test]
+fn wide_metadata_spans_several_pages() {
+    let columns: Vec<Column> = (0..3000)
+        .map(|i| match i % 2 {
+            0 => Column::new(format!("COL{}", i).into(), [i as f64, -1.0]),
+            _ => Column::new(format!("COL{}", i).into(), [format!("x{}", i), String::new()]),
+        })
+        .collect();
+    let df = DataFrame::new(columns).unwrap();
+    let options = (0..3000).fold(SasWriteOptions::new(), |options, i| {
+        options.with_label(&format!("COL{}", i), &format!("Label of the column number {}", i))
+    });
+    let file = write(&[df.clone()], options).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let metadata = SasReader::open(path, SasReadOptions::new()).unwrap().metadata().unwrap();
+    assert_eq!(metadata.columns.len(), 3000);
+    assert_eq!(metadata.columns[2999].label, "Label of the column number 2999");
+    assert!(read_all(path, SasReadOptions::new()).unwrap().equals_missing(&df));
+}
+
+#[test]
+fn wide_string_columns_read_back() {
+    // Mostly short values in a column of the longest SAS length, a few filling it
+    let notes: Vec<String> = (0..2000)
+        .map(|i| match i % 500 {
+            0 => "x".repeat(32767),
+            _ => format!("note {}", i),
+        })
+        .collect();
+    let df = df!("ID" => (0..2000).map(f64::from).collect::<Vec<_>>(), "NOTE" => notes).unwrap();
+    let file = write(&[df.clone()], SasWriteOptions::new().with_string_length("NOTE", 32767)).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let metadata = SasReader::open(path, SasReadOptions::new()).unwrap().metadata().unwrap();
+    assert_eq!(metadata.columns[1].length, 32767);
+    // A chunk larger than the file: its full length would reserve gigabytes per column
+    for layout in [SasStringLayout::Utf8, SasStringLayout::Utf8View] {
+        let options = SasReadOptions::new().with_chunk_size(1_000_000).with_string_layout(layout);
+        let read = read_all(path, options).unwrap();
+        let read = read.lazy().with_column(col("NOTE").cast(DataType::String)).collect().unwrap();
+        assert!(read.equals(&df), "{:?}", layout);
+    }
+}
+
+#[test]
+fn invalid_batches_fail() {
+    let file = tempfile::Builder::new().suffix(".sas7bdat").tempfile().unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let mut writer = SasWriter::create(path, SasWriteOptions::new()).unwrap();
+    writer.write_batch(&df!("NAME" => ["abc"]).unwrap()).unwrap();
+    assert!(matches!(writer.write_batch(&df!("OTHER" => ["abc"]).unwrap()), Err(PolarsError::SchemaMismatch(_))));
+    assert!(writer.write_batch(&df!("NAME" => ["abcd"]).unwrap()).is_err());
+
+    let mut writer = SasWriter::create(path, SasWriteOptions::new().with_truncation(true)).unwrap();
+    writer.write_batch(&df!("NAME" => ["abc"]).unwrap()).unwrap();
+    writer.write_batch(&df!("NAME" => ["abcd"]).unwrap()).unwrap();
+    writer.finish().unwrap();
+    assert!(read_all(path, SasReadOptions::new()).unwrap().equals(&df!("NAME" => ["abc", "abc"]).unwrap()));
+
+    let long_name = "A".repeat(33);
+    assert!(write(&[df!(long_name.as_str() => [1.0]).unwrap()], SasWriteOptions::new()).is_err());
+    assert!(write(&[df!("a" => [1.0], "A" => [2.0]).unwrap()], SasWriteOptions::new()).is_err());
+    assert!(write(&[], SasWriteOptions::new()).is_err());
+}
@@ -0,0 +1,350 @@
+use std::io::Write;
+
+use cpp_sas7bdat::{
+    write_xpt, SasBatchIterator, SasFileFormat, SasReadOptions, SasReader, SasRowIndexType, SasXportVersion,
+    SasXportWriteOptions,
+};
+use polars::prelude::*;
+
+mod common;
+use common::collect;
+
+// IBM System/370 doubles from the IBM floating point reference examples
+const IBM_ONE: [u8; 8] = [0x41, 0x10, 0, 0, 0, 0, 0, 0];
+const IBM_MINUS_118_625: [u8; 8] = [0xC2, 0x76, 0xA0, 0, 0, 0, 0, 0];
+const IBM_HALF: [u8; 8] = [0x40, 0x80, 0, 0, 0, 0, 0, 0];
+// 22000 days after 1960-01-01: 2020-03-26
+const IBM_22000: [u8; 8] = [0x44, 0x55, 0xF0, 0, 0, 0, 0, 0];
+const MISSING: [u8; 8] = [b'.', 0, 0, 0, 0, 0, 0, 0];
+const MISSING_A: [u8; 8] = [b'A', 0, 0, 0, 0, 0, 0, 0];
+
+fn header(name: &str, numbers: &str) -> Vec<u8> {
+    format!("HEADER RECORD*******{:<8}HEADER RECORD!!!!!!!{:<30}  ", name, numbers).into_bytes()
+}
+
+fn pad(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.resize(bytes.len().next_multiple_of(80), b' ');
+    bytes
+}
+
+struct Variable {
+    name: &'static str,
+    label: &'static str,
+    format: &'static str,
+    width: u16,
+    numeric: bool,
+    length: u16,
+}
+
+fn namestr(variable: &Variable, number: u16, position: u32, v8: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(140);
+    bytes.extend_from_slice(&(if variable.numeric { 1u16 } else { 2 }).to_be_bytes());
+    bytes.extend_from_slice(&0u16.to_be_bytes());
+    bytes.extend_from_slice(&variable.length.to_be_bytes());
+    bytes.extend_from_slice(&number.to_be_bytes());
+    let short_name: String = variable.name.chars().take(8).collect();
+    let short_label: String = variable.label.chars().take(40).collect();
+    bytes.extend_from_slice(format!("{:<8}{:<40}{:<8}", short_name, short_label, variable.format).as_bytes());
+    bytes.extend_from_slice(&variable.width.to_be_bytes());
+    bytes.extend_from_slice(&[0; 6]);
+    bytes.extend_from_slice(format!("{:<8}", "").as_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&position.to_be_bytes());
+    if v8 {
+        bytes.extend_from_slice(format!("{:<32}", variable.name).as_bytes());
+        bytes.extend_from_slice(&(variable.label.len() as u16).to_be_bytes());
+    }
+    bytes.resize(140, 0);
+    bytes
+}
+
+// A transport library with one member, `rows` being the raw observations
+fn xport(v8: bool, dataset: &str, label: &str, variables: &[Variable], rows: &[u8]) -> Vec<u8> {
+    let created = "01JAN24:00:00:00";
+    let mut file = header(if v8 { "LIBV8" } else { "LIBRARY" }, &"0".repeat(30));
+    file.extend(format!("SAS     SAS     SASLIB  9.4     X64_10PR{:<24}{}", "", created).into_bytes());
+    file.extend(format!("{}{:<64}", created, "").into_bytes());
+
+    file.extend(header(if v8 { "MEMBV8" } else { "MEMBER" }, "000000000000000001600000000140"));
+    file.extend(header(if v8 { "DSCPTV8" } else { "DSCRPTR" }, &"0".repeat(30)));
+    match v8 {
+        true => file.extend(format!("SAS     {:<32}SASDATA 9.4     X64_10PR{}", dataset, created).into_bytes()),
+        false => file.extend(format!("SAS     {:<8}SASDATA 9.4     X64_10PR{:<24}{}", dataset, "", created).into_bytes()),
+    }
+    file.extend(format!("{}{:<16}{:<40}{:<8}", created, "", label, "").into_bytes());
+
+    file.extend(header(if v8 { "NAMSTV8" } else { "NAMESTR" }, &format!("00000{:05}{}", variables.len(), "0".repeat(20))));
+    let mut namestrs = Vec::new();
+    let mut position = 0;
+    for (i, variable) in variables.iter().enumerate() {
+        namestrs.extend(namestr(variable, i as u16 + 1, position, v8));
+        position += variable.length as u32;
+    }
+    file.extend(pad(namestrs));
+
+    let long_labels: Vec<_> = variables.iter().enumerate().filter(|(_, v)| v8 && v.label.len() > 40).collect();
+    if !long_labels.is_empty() {
+        file.extend(header("LABELV8", &long_labels.len().to_string()));
+        let mut labels = Vec::new();
+        for (i, variable) in long_labels {
+            labels.extend_from_slice(&(i as u16 + 1).to_be_bytes());
+            labels.extend_from_slice(&(variable.name.len() as u16).to_be_bytes());
+            labels.extend_from_slice(&(variable.label.len() as u16).to_be_bytes());
+            labels.extend_from_slice(variable.name.as_bytes());
+            labels.extend_from_slice(variable.label.as_bytes());
+        }
+        file.extend(pad(labels));
+    }
+
+    file.extend(header(if v8 { "OBSV8" } else { "OBS" }, &"0".repeat(30)));
+    file.extend(pad(rows.to_vec()));
+    file
+}
+
+fn demographics(v8: bool) -> Vec<u8> {
+    let variables = [
+        Variable { name: "ID", label: "Subject", format: "", width: 0, numeric: true, length: 8 },
+        Variable { name: "NAME", label: "", format: "$", width: 8, numeric: false, length: 8 },
+        Variable { name: "VISIT", label: "Visit date", format: "DATE", width: 9, numeric: true, length: 8 },
+    ];
+    let mut rows = Vec::new();
+    for (id, name, visit) in [
+        (IBM_ONE, "Alice", IBM_22000),
+        (IBM_MINUS_118_625, "Bob", MISSING),
+        (IBM_HALF, "", MISSING_A),
+    ] {
+        rows.extend_from_slice(&id);
+        rows.extend_from_slice(format!("{:<8}", name).as_bytes());
+        rows.extend_from_slice(&visit);
+    }
+    xport(v8, "DM", "Demographics", &variables, &rows)
+}
+
+fn write_temp(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".xpt").tempfile().unwrap();
+    file.write_all(bytes).unwrap();
+    file
+}
+
+#[test]
+fn reads_v5_transport_files() {
+    let file = write_temp(&demographics(false));
+    let path = file.path().to_str().unwrap();
+
+    let mut reader = SasReader::open(path, SasReadOptions::new()).unwrap();
+    assert_eq!(reader.file_format(), SasFileFormat::XportV5);
+    let schema = reader.get_schema().unwrap().clone();
+    assert_eq!(
+        schema.iter().map(|(name, dtype)| (name.to_string(), dtype.clone())).collect::<Vec<_>>(),
+        [
+            ("ID".to_string(), DataType::Float64),
+            ("NAME".to_string(), DataType::String),
+            ("VISIT".to_string(), DataType::Date),
+        ]
+    );
+    assert_eq!(reader.get_column_info(2).unwrap(), ("VISIT".to_string(), "date32[day]".to_string()));
+
+    let metadata = reader.metadata().unwrap();
+    assert_eq!(metadata.dataset_name, "DM");
+    assert_eq!(metadata.label, "Demographics");
+    assert_eq!(metadata.sas_release, "9.4");
+    assert_eq!(metadata.columns[0].label, "Subject");
+    assert_eq!((metadata.columns[2].format.as_str(), metadata.columns[2].format_width), ("DATE", 9));
+
+    let df = collect(SasBatchIterator::open(path, SasReadOptions::new()).unwrap()).unwrap();
+    assert_eq!(df.height(), 3);
+    let id: Vec<_> = df.column("ID").unwrap().f64().unwrap().into_iter().collect();
+    assert_eq!(id, [Some(1.0), Some(-118.625), Some(0.5)]);
+    let name: Vec<_> = df.column("NAME").unwrap().str().unwrap().into_iter().collect();
+    assert_eq!(name, [Some("Alice"), Some("Bob"), Some("")]);
+    let visit = df.column("VISIT").unwrap().cast(&DataType::Int32).unwrap();
+    let visit: Vec<_> = visit.i32().unwrap().into_iter().collect();
+    assert_eq!(visit, [Some(22000 - 3653), None, None]);
+}
+
+#[test]
+fn reads_v8_long_names_and_labels() {
+    let long_label = "Identifier of the subject in the clinical study database";
+    let variables = [
+        Variable { name: "SUBJECT_IDENTIFIER", label: long_label, format: "", width: 0, numeric: true, length: 8 },
+        Variable { name: "ARM", label: "Arm", format: "$", width: 4, numeric: false, length: 4 },
+    ];
+    let mut rows = Vec::new();
+    rows.extend_from_slice(&IBM_ONE);
+    rows.extend_from_slice(b"A   ");
+    let bytes = xport(true, "ADSL_WITH_A_LONG_NAME", "Subject level", &variables, &rows);
+
+    let mut reader = SasReader::from_bytes(bytes, SasReadOptions::new()).unwrap();
+    assert_eq!(reader.file_format(), SasFileFormat::XportV8);
+    let metadata = reader.metadata().unwrap();
+    assert_eq!(metadata.dataset_name, "ADSL_WITH_A_LONG_NAME");
+    assert_eq!(metadata.columns[0].name, "SUBJECT_IDENTIFIER");
+    assert_eq!(metadata.columns[0].label, long_label);
+
+    let df = reader.read_next_batch().unwrap();
+    assert_eq!(df.column("SUBJECT_IDENTIFIER").unwrap().f64().unwrap().get(0), Some(1.0));
+    assert_eq!(df.column("ARM").unwrap().str().unwrap().get(0), Some("A"));
+    assert!(reader.read_next_batch().is_err());
+}
+
+#[test]
+fn padding_blanks_are_not_rows() {
+    // 3 rows of 8 bytes, the last record ends with 56 blanks
+    let variables = [Variable { name: "X", label: "", format: "", width: 0, numeric: true, length: 8 }];
+    let rows = [IBM_ONE, IBM_HALF, MISSING].concat();
+    let mut reader = SasReader::from_bytes(xport(false, "ONE", "", &variables, &rows), SasReadOptions::new()).unwrap();
+
+    let df = reader.read_next_batch().unwrap();
+    let x: Vec<_> = df.column("X").unwrap().f64().unwrap().into_iter().collect();
+    assert_eq!(x, [Some(1.0), Some(0.5), None]);
+}
+
+#[test]
+fn batches_number_and_locate_their_rows() {
+    let options = SasReadOptions::new()
+        .with_chunk_size(2)
+        .with_row_index("row_nr", 0, SasRowIndexType::UInt32);
+    let batches: Vec<_> = SasBatchIterator::from_bytes(demographics(false), options)
+        .unwrap()
+        .sas_batches()
+        .collect::<PolarsResult<_>>()
+        .unwrap();
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!((batches[0].first_row, batches[1].first_row), (0, 2));
+    assert_eq!(batches[1].df.column("row_nr").unwrap().u32().unwrap().get(0), Some(2));
+    // Headers: 3 library, 4 member, 1 NAMESTR header, 6 records of 3 namestrs, 1 OBS header
+    assert_eq!(batches[0].page_range, 15..16);
+    assert_eq!(batches[1].page_range, 15..16);
+
+    let mut reader = SasReader::from_bytes(demographics(false), SasReadOptions::new().with_chunk_size(2)).unwrap();
+    let first = reader.read_next_batch().unwrap();
+    reader.read_next_batch().unwrap();
+    reader.reset().unwrap();
+    assert!(reader.read_next_batch().unwrap().equals_missing(&first));
+}
+
+#[test]
+fn truncated_transport_files_fail() {
+    let bytes = demographics(false);
+    assert!(SasReader::from_bytes(bytes[..400].to_vec(), SasReadOptions::new()).is_err());
+}
+
+fn write_and_read(df: &DataFrame, options: SasXportWriteOptions) -> PolarsResult<(SasReader, Vec<u8>)> {
+    let file = tempfile::Builder::new().suffix(".xpt").tempfile().unwrap();
+    let path = file.path().to_str().unwrap();
+    write_xpt(df, path, options)?;
+    let bytes = std::fs::read(path).unwrap();
+    Ok((SasReader::from_bytes(bytes.clone(), SasReadOptions::new())?, bytes))
+}
+
+#[test]
+fn written_files_read_back() {
+    let df = df!(
+        "ID" => [1i64, 2, 3],
+        "WEIGHT" => [Some(70.5), None, Some(f64::NAN)],
+        "NAME" => [Some("Alice"), None, Some("Bob")],
+        "ACTIVE" => [true, false, true],
+        "VISIT" => [Some(18347), None, Some(-3653)],
+        "TAKEN" => [Some(1_585_180_800_123_456i64), Some(0), None],
+        "AT" => [Some(3_600_000_000_000i64), Some(45_296_000_000_000), None],
+    ).unwrap();
+    let df = df.lazy().with_columns([
+        col("VISIT").cast(DataType::Date),
+        col("TAKEN").cast(DataType::Datetime(TimeUnit::Microseconds, None)),
+        col("AT").cast(DataType::Time),
+    ]).collect().unwrap();
+
+    let options = SasXportWriteOptions::new()
+        .with_dataset_name("DM")
+        .with_dataset_label("Demographics")
+        .with_label("WEIGHT", "Weight (kg)")
+        .with_format("WEIGHT", "8.1");
+    let (mut reader, bytes) = write_and_read(&df, options).unwrap();
+    assert_eq!(bytes.len() % 80, 0);
+    assert_eq!(reader.file_format(), SasFileFormat::XportV5);
+
+    let metadata = reader.metadata().unwrap();
+    assert_eq!((metadata.dataset_name.as_str(), metadata.label.as_str()), ("DM", "Demographics"));
+    assert_eq!(metadata.columns[1].label, "Weight (kg)");
+    let formats: Vec<_> = metadata.columns.iter()
+        .map(|column| (column.format.as_str(), column.format_width, column.format_decimals))
+        .collect();
+    assert_eq!(formats, [("", 0, 0), ("", 8, 1), ("", 0, 0), ("", 0, 0), ("DATE", 9, 0), ("DATETIME", 20, 0), ("TIME", 8, 0)]);
+    assert_eq!(metadata.columns[2].length, 5);
+
+    let read = reader.read_next_batch().unwrap();
+    let expected = df.lazy().with_columns([
+        col("ID").cast(DataType::Float64),
+        col("ACTIVE").cast(DataType::Float64),
+        col("WEIGHT").fill_nan(lit(NULL)),
+        col("NAME").fill_null(lit("")),
+    ]).collect().unwrap();
+    assert!(read.equals_missing(&expected), "{read} != {expected}");
+}
+
+#[test]
+fn written_numbers_are_ibm_doubles() {
+    let df = df!("X" => [1.0, -118.625, 0.5, 22000.0]).unwrap();
+    let (_, bytes) = write_and_read(&df, SasXportWriteOptions::new()).unwrap();
+    let data = &bytes[bytes.len() - 80..];
+    assert_eq!(&data[..32], [IBM_ONE, IBM_MINUS_118_625, IBM_HALF, IBM_22000].concat());
+    assert!(data[32..].iter().all(|&b| b == b' '));
+    assert_eq!(&bytes[..80], header("LIBRARY", &"0".repeat(30)).as_slice());
+}
+
+#[test]
+fn written_numbers_survive_the_ibm_conversion() {
+    let values = [0.1, -1e-30, 123_456_789.123_456_78, 1e75, -5.4e-70, 1e-300, f64::MIN_POSITIVE];
+    let df = df!("X" => values).unwrap();
+    let (mut reader, _) = write_and_read(&df, SasXportWriteOptions::new()).unwrap();
+    let read: Vec<_> = reader.read_next_batch().unwrap().column("X").unwrap().f64().unwrap().into_no_null_iter().collect();
+    for (value, read) in values.iter().zip(read) {
+        let tolerance = if value.abs() < 5.4e-79 { 5.4e-79 } else { value.abs() * 1e-15 };
+        assert!((value - read).abs() <= tolerance, "{value} read as {read}");
+    }
+
+    assert!(write_and_read(&df!("X" => [1e76]).unwrap(), SasXportWriteOptions::new()).is_err());
+    assert!(write_and_read(&df!("X" => [f64::INFINITY]).unwrap(), SasXportWriteOptions::new()).is_err());
+}
+
+#[test]
+fn v5_limits_fail_or_truncate() {
+    let df = df!("SUBJECT_IDENTIFIER" => ["a".repeat(201)]).unwrap();
+    let error = write_and_read(&df, SasXportWriteOptions::new()).unwrap_err();
+    assert!(error.to_string().contains("SUBJECT_IDENTIFIER"), "{error}");
+    let long_label = SasXportWriteOptions::new().with_label("X", &"l".repeat(41));
+    assert!(write_and_read(&df!("X" => [1.0]).unwrap(), long_label).is_err());
+
+    let options = SasXportWriteOptions::new().with_truncation(true).with_label("SUBJECT_IDENTIFIER", &"l".repeat(41));
+    let (mut reader, _) = write_and_read(&df, options).unwrap();
+    let metadata = reader.metadata().unwrap();
+    assert_eq!(metadata.columns[0].name, "SUBJECT_");
+    assert_eq!(metadata.columns[0].label, "l".repeat(40));
+    assert_eq!(reader.read_next_batch().unwrap().column("SUBJECT_").unwrap().str().unwrap().get(0), Some("a".repeat(200).as_str()));
+
+    // Names truncated to the same prefix
+    let df = df!("SUBJECT_A" => [1.0], "SUBJECT_B" => [2.0]).unwrap();
+    assert!(write_and_read(&df, SasXportWriteOptions::new().with_truncation(true)).is_err());
+}
+
+#[test]
+fn v8_files_keep_long_names_and_labels() {
+    let long_label = "Identifier of the subject in the clinical study database";
+    let df = df!("SUBJECT_IDENTIFIER" => [1.0, 2.0], "ARM" => ["Placebo", "Drug"]).unwrap();
+    let options = SasXportWriteOptions::new()
+        .with_version(SasXportVersion::V8)
+        .with_dataset_name("ADSL_WITH_A_LONG_NAME")
+        .with_label("SUBJECT_IDENTIFIER", long_label);
+    let (mut reader, _) = write_and_read(&df, options).unwrap();
+    assert_eq!(reader.file_format(), SasFileFormat::XportV8);
+
+    let metadata = reader.metadata().unwrap();
+    assert_eq!(metadata.dataset_name, "ADSL_WITH_A_LONG_NAME");
+    assert_eq!(metadata.columns[0].label, long_label);
+    assert!(reader.read_next_batch().unwrap().equals_missing(&df));
+
+    // Written back with the metadata read
+    let (mut copy, _) = write_and_read(&df, SasXportWriteOptions::new().with_version(SasXportVersion::V8).with_metadata(&metadata)).unwrap();
+    assert_eq!(copy.metadata().unwrap().columns, metadata.columns);
+}